@@ -5,145 +5,476 @@ use ink_lang as ink;
 #[ink::contract(dynamic_storage_allocator = true)]
 mod merkle {
     use ink_env::hash::{HashOutput, Sha2x256};
+    use ink_prelude::vec::Vec as ProofVec;
     use ink_storage::{Box, Vec};
 
+    /// The number of recent roots kept by default.
+    const DEFAULT_ROOT_HISTORY_SIZE: u32 = 30;
+
+    /// The largest depth whose capacity of `2^depth` elements fits in a
+    /// `u32`.
+    const MAX_DEPTH: u32 = 31;
+
+    /// Prepended to the data of a leaf before hashing it.
+    const LEAF_PREFIX: u8 = 0x00;
+
+    /// Prepended to the concatenated children of a node before hashing it.
+    const NODE_PREFIX: u8 = 0x01;
+
+    /// Errors that can occur when operating on the tree.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the tree already holds `2^depth` elements.
+        TreeFull,
+        /// Returned if no element has been added at the given index, or
+        /// there is no node at the given position.
+        IndexOutOfBounds,
+        /// Returned if the caller is not allowed to modify the tree.
+        Unauthorized,
+        /// Returned if a proof does not have the shape of a proof for this
+        /// tree, e.g. it has the wrong number of siblings.
+        InvalidProof,
+        /// Returned if the element at the given index has been cleared.
+        LeafCleared,
+    }
+
+    /// The result type of the contract's messages.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A proof of membership for one element of the tree.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct Proof {
+        /// The position of the element.
+        pub leaf_index: u32,
+        /// The hash of the element.
+        pub leaf: Hash,
+        /// The root of the tree when the proof was generated.
+        pub root: Hash,
+        /// The sibling hashes on the path from the element up to the root,
+        /// starting with the element's own sibling.
+        pub siblings: ProofVec<Hash>,
+    }
+
+    /// Emitted when an element is added to the tree.
+    #[ink(event)]
+    pub struct LeafInserted {
+        #[ink(topic)]
+        index: u32,
+        #[ink(topic)]
+        leaf_hash: Hash,
+        data: Balance,
+    }
+
+    /// Emitted when an element that was already added is replaced or
+    /// cleared, in which case data is `None`.
+    #[ink(event)]
+    pub struct LeafUpdated {
+        #[ink(topic)]
+        index: u32,
+        #[ink(topic)]
+        leaf_hash: Hash,
+        data: Option<Balance>,
+    }
+
+    /// Emitted whenever the root changes.
+    #[ink(event)]
+    pub struct RootUpdated {
+        #[ink(topic)]
+        old_root: Hash,
+        #[ink(topic)]
+        new_root: Hash,
+        size: u32,
+    }
+
+    /// Emitted when the last free position of the tree is filled.
+    #[ink(event)]
+    pub struct TreeFull {
+        capacity: u32,
+    }
+
     /// Defines the storage of a merkle contract.
     #[ink(storage)]
     pub struct MerkleTree {
+        /// The account allowed to modify the tree.
+        owner: AccountId,
         depth: u32,
-        root_hash: Hash,
-        data: Vec<Balance>,
+        /// None for elements that have been cleared.
+        data: Vec<Option<Balance>>,
+        /// tree[d] holds the nodes written so far on level d, level 0
+        /// holding the root and level `depth` the leaves. Every node past
+        /// the end of its row is the root of an empty subtree.
         tree: Vec<Box<Vec<Hash>>>,
+        /// zeros[h] is the hash of an empty subtree of height h.
+        zeros: Vec<Hash>,
         index: u32,
+        /// Ring buffer of the most recent roots.
+        root_history: Vec<Hash>,
+        root_history_size: u32,
+        root_history_index: u32,
     }
 
     impl MerkleTree {
-        /// Initializes the merkle tree.
+        /// Initializes the merkle tree, keeping the default number of
+        /// recent roots. The caller becomes the owner of the tree.
         #[ink(constructor)]
-        pub fn new(depth: u32, root_hash: Hash) -> Self {
+        pub fn new(depth: u32) -> Self {
+            Self::with_root_history(depth, DEFAULT_ROOT_HISTORY_SIZE)
+        }
+
+        /// Initializes the merkle tree, keeping the last
+        /// `root_history_size` roots (at least one).
+        ///
+        /// Panics if depth is larger than 31.
+        #[ink(constructor)]
+        pub fn with_root_history(depth: u32, root_history_size: u32) -> Self {
+            assert!(depth <= MAX_DEPTH, "depth must be at most 31");
+
             let mut mt = Self {
+                owner: Self::env().caller(),
                 depth: depth,
-                root_hash: root_hash,
                 data: Vec::new(),
                 tree: Vec::new(),
+                zeros: Vec::new(),
                 index: 0,
+                root_history: Vec::new(),
+                root_history_size: core::cmp::max(root_history_size, 1),
+                root_history_index: 0,
             };
 
-            // // push root node
-            let mut root_row = Vec::new();
-            root_row.push(calculate_hash(Balance::from(0u128)));
-            let b = Box::new(root_row);
-            mt.tree.push(b);
-
-            // println!("{:#?}", mt);
-
-            // build intermediate nodes up to root
-            for d in (0..depth).rev() {
-                let mut t_row = Vec::new();
-                for i in 0..(u32::pow(2, d)) {
-                    // println!("d = {} i = {}", d, i);
-                    t_row.push(concat_hash(
-                        &mt.tree[d + 1][2 * i],
-                        &mt.tree[d + 1][2 * i + 1],
-                    ));
-                    // println!("{:#?}", mt.tree[d]);
-                }
-                // println!("{:#?}", mt.tree);
-                mt.tree.push(Box::new(t_row));
+            // the rows start out empty, so only the empty subtree of every
+            // height has to be hashed and stored
+            let mut zero = empty_hash();
+            for _ in 0..depth {
+                mt.zeros.push(zero);
+                mt.tree.push(Box::new(Vec::new()));
+                zero = concat_hash(&zero, &zero);
             }
+            mt.zeros.push(zero);
+            mt.tree.push(Box::new(Vec::new()));
+
+            mt.push_root(zero);
 
             mt
         }
 
         /// Adds an element to the tree. Elements are added sequentially.
         #[ink(message)]
-        pub fn add_data(&mut self, data: Balance) {
-            if self.index == u32::pow(2, self.depth as u32) {
-                return; // error
+        pub fn add_data(&mut self, data: Balance) -> Result<()> {
+            self.ensure_owner()?;
+            if self.index == u32::pow(2, self.depth) {
+                return Err(Error::TreeFull);
             }
-            self.data.push(data);
-            self.tree[self.depth][self.index] = calculate_hash(data);
+            let old_root = self.root();
+            let index = self.index;
+            let leaf_hash = calculate_hash(data);
+            self.data.push(Some(data));
+            self.update_path(index, leaf_hash);
 
-            let mut i = self.index;
-            let mut d = self.depth;
-            while i % 2 == 1 {
-                i = i / 2;
-                d = d - 1;
-                self.tree[d][i] =
-                    concat_hash(&self.tree[d + 1][2 * i], &self.tree[d + 1][2 * i + 1]);
+            self.index = self.index + 1;
+
+            self.env().emit_event(LeafInserted {
+                index,
+                leaf_hash,
+                data,
+            });
+            self.record_root(old_root);
+            if self.index == u32::pow(2, self.depth) {
+                self.env().emit_event(TreeFull {
+                    capacity: self.index,
+                });
             }
 
-            self.index = self.index + 1;
+            Ok(())
         }
 
-        /// Verifies that the data at position index is in the tree.
+        /// Replaces the element at position index, which must already have
+        /// been added, and returns the old and new root.
         #[ink(message)]
-        pub fn verify(&self, data: Balance, index: u32) -> bool {
-            let proof = self.generate_proof(index);
-            let mut hash = calculate_hash(data);
+        pub fn update_leaf(&mut self, index: u32, data: Balance) -> Result<(Hash, Hash)> {
+            self.replace_leaf(index, Some(data), calculate_hash(data))
+        }
 
-            for d in (1..self.depth + 1).rev() {
-                if proof[d].1 {
-                    hash = concat_hash(&hash, &proof[d].0);
+        /// Resets the element at position index to an empty leaf, which
+        /// holds no data, and returns the old and new root.
+        #[ink(message)]
+        pub fn clear_leaf(&mut self, index: u32) -> Result<(Hash, Hash)> {
+            self.replace_leaf(index, None, self.zeros[0])
+        }
+
+        /// Returns true if root is the current root or one of the recent
+        /// roots.
+        #[ink(message)]
+        pub fn is_known_root(&self, root: Hash) -> bool {
+            self.root_history.iter().any(|r| *r == root)
+        }
+
+        /// Verifies a proof built off-chain that leaf is the hash stored at
+        /// position index, against the current root or one of the recent
+        /// roots. siblings[i] holds the sibling hash at height i, starting
+        /// from the leaf; whether it is on the left or the right follows
+        /// from index.
+        ///
+        /// Returns `Error::InvalidProof` if the number of siblings does not
+        /// match the depth of the tree or index is out of range.
+        #[ink(message)]
+        pub fn verify_proof(
+            &self,
+            leaf: Hash,
+            index: u32,
+            siblings: ProofVec<Hash>,
+        ) -> Result<bool> {
+            if siblings.len() != self.depth as usize {
+                return Err(Error::InvalidProof);
+            }
+            if index >> self.depth != 0 {
+                return Err(Error::InvalidProof);
+            }
+
+            let mut hash = leaf;
+            let mut i = index;
+            for sibling in siblings.iter() {
+                if i % 2 == 0 {
+                    hash = concat_hash(&hash, sibling);
                 } else {
-                    hash = concat_hash(&proof[d].0, &hash);
+                    hash = concat_hash(sibling, &hash);
                 }
+                i = i / 2;
             }
 
-            hash == proof[0].0
+            Ok(self.is_known_root(hash))
+        }
+
+        /// Like `verify_proof`, but hashes data into the leaf first.
+        #[ink(message)]
+        pub fn verify_proof_data(
+            &self,
+            data: Balance,
+            index: u32,
+            siblings: ProofVec<Hash>,
+        ) -> Result<bool> {
+            self.verify_proof(calculate_hash(data), index, siblings)
         }
 
-        /// Returns a vec of size depth + 1 with proof[i] containing
-        /// a tuple with the hash of the node at depth i and a boolean
-        /// indicating whether the hash is left (false) or right (true)
-        /// that is needed for proving membership of data at element index.
+        /// Returns the account allowed to modify the tree.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns the current root of the tree.
+        #[ink(message)]
+        pub fn root(&self) -> Hash {
+            self.node_hash(0, 0)
+        }
+
+        /// Returns the depth of the tree.
+        #[ink(message)]
+        pub fn depth(&self) -> u32 {
+            self.depth
+        }
+
+        /// Returns the number of elements added so far.
+        #[ink(message)]
+        pub fn len(&self) -> u32 {
+            self.index
+        }
+
+        /// Returns the maximum number of elements the tree can hold.
+        #[ink(message)]
+        pub fn capacity(&self) -> u32 {
+            u32::pow(2, self.depth)
+        }
+
+        /// Returns the element at position index.
         ///
-        /// The root hash at depth 0 is included so that the index into
-        /// the proof vector corresponds to the depth. So, for example,
-        /// if the tree has depth 3, then proof[2] contains the hash
-        /// of the node at depth 2 needed for the proof.
+        /// Returns `Error::IndexOutOfBounds` if no element has been added
+        /// at index, or `Error::LeafCleared` if it has been cleared.
+        #[ink(message)]
+        pub fn leaf(&self, index: u32) -> Result<Balance> {
+            match self.data.get(index) {
+                Some(Some(data)) => Ok(*data),
+                Some(None) => Err(Error::LeafCleared),
+                None => Err(Error::IndexOutOfBounds),
+            }
+        }
+
+        /// Returns the hash of the node at position index on level depth,
+        /// where level 0 holds the root and level `depth()` the leaves.
         ///
-        /// TODO: memoize
-        fn generate_proof(&self, index: u32) -> Vec<(Hash, bool)> {
-            if index >= self.index {
-                return Vec::new(); // error
+        /// Returns `Error::IndexOutOfBounds` if there is no such node.
+        #[ink(message)]
+        pub fn node(&self, depth: u32, index: u32) -> Result<Hash> {
+            if depth > self.depth || index >> depth != 0 {
+                return Err(Error::IndexOutOfBounds);
             }
 
-            let mut proof = Vec::new();
+            Ok(self.node_hash(depth, index))
+        }
 
-            // add root hash
-            proof.push((self.root_hash, true));
+        /// Returns a proof of membership for the element at position index
+        /// against the current root, which can be checked with
+        /// `verify_proof`.
+        #[ink(message)]
+        pub fn proof(&self, index: u32) -> Result<Proof> {
+            let siblings = self.generate_proof(index)?;
+
+            Ok(Proof {
+                leaf_index: index,
+                leaf: self.node_hash(self.depth, index),
+                root: self.root(),
+                siblings,
+            })
+        }
 
+        /// Returns the sibling hashes on the path from the element at
+        /// position index up to the root, starting with the element's own
+        /// sibling.
+        ///
+        /// Returns `Error::IndexOutOfBounds` if no element has been
+        /// added at index.
+        fn generate_proof(&self, index: u32) -> Result<ProofVec<Hash>> {
+            if index >= self.index {
+                return Err(Error::IndexOutOfBounds);
+            }
+
+            let mut siblings = ProofVec::new();
             let mut i = index;
-            // add non-root hashes
             for d in (1..self.depth + 1).rev() {
-                // println!("i: {} d: {} i % 2: {}", i, d, i % 2);
-                let elem = if i % 2 == 0 {
-                    (self.tree[d][i + 1], true)
-                } else {
-                    (self.tree[d][i - 1], false)
-                };
-                proof.push(elem);
-                // println!("proof: {:#?}", proof);
+                siblings.push(self.node_hash(d, i ^ 1));
                 i = i / 2;
             }
 
-            proof
+            Ok(siblings)
+        }
+
+        /// Stores data at position index, which must already have been
+        /// added, with the given leaf hash and returns the old and new root.
+        /// data is `None` when the element is cleared.
+        fn replace_leaf(
+            &mut self,
+            index: u32,
+            data: Option<Balance>,
+            leaf_hash: Hash,
+        ) -> Result<(Hash, Hash)> {
+            self.ensure_owner()?;
+            if index >= self.index {
+                return Err(Error::IndexOutOfBounds);
+            }
+
+            let old_root = self.root();
+            self.data[index] = data;
+            self.update_path(index, leaf_hash);
+
+            self.env().emit_event(LeafUpdated {
+                index,
+                leaf_hash,
+                data,
+            });
+            let new_root = self.record_root(old_root);
+
+            Ok((old_root, new_root))
+        }
+
+        /// Returns `Error::Unauthorized` unless the caller is the owner.
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+
+            Ok(())
+        }
+
+        /// Stores the hash of the leaf at position index and recomputes the
+        /// nodes on its path up to the root.
+        fn update_path(&mut self, index: u32, hash: Hash) {
+            self.set_node(self.depth, index, hash);
+
+            let mut i = index;
+            for d in (0..self.depth).rev() {
+                i = i / 2;
+                let hash = concat_hash(
+                    &self.node_hash(d + 1, 2 * i),
+                    &self.node_hash(d + 1, 2 * i + 1),
+                );
+                self.set_node(d, i, hash);
+            }
+        }
+
+        /// Returns the hash of the node at position index on level depth,
+        /// falling back to the empty subtree if it has not been written.
+        fn node_hash(&self, depth: u32, index: u32) -> Hash {
+            match self.tree[depth].get(index) {
+                Some(hash) => *hash,
+                None => self.zeros[self.depth - depth],
+            }
+        }
+
+        /// Stores the hash of the node at position index on level depth.
+        /// Elements are added sequentially, so a node that has not been
+        /// written yet is always the next one in its row.
+        fn set_node(&mut self, depth: u32, index: u32, hash: Hash) {
+            let row = &mut self.tree[depth];
+            if index < row.len() {
+                row[index] = hash;
+            } else {
+                row.push(hash);
+            }
+        }
+
+        /// Records the current root in the history and announces the
+        /// change from old_root, returning the current root.
+        fn record_root(&mut self, old_root: Hash) -> Hash {
+            let new_root = self.root();
+            self.push_root(new_root);
+            self.env().emit_event(RootUpdated {
+                old_root,
+                new_root,
+                size: self.index,
+            });
+
+            new_root
+        }
+
+        /// Records root as the newest root, overwriting the oldest one
+        /// once the history is full.
+        fn push_root(&mut self, root: Hash) {
+            if self.root_history.len() < self.root_history_size {
+                self.root_history.push(root);
+            } else {
+                self.root_history[self.root_history_index] = root;
+            }
+            self.root_history_index = (self.root_history_index + 1) % self.root_history_size;
         }
     }
 
-    // Helper to calculate a hash value.
+    // Helper to calculate the hash of a leaf holding data. Leaves and nodes
+    // are hashed like the `Sha256` hasher of the off-chain library does, so
+    // roots and proofs can be checked against it.
     fn calculate_hash(data: Balance) -> Hash {
-        let mut output = <Sha2x256 as HashOutput>::Type::default(); // 256-bit buffer
-        ink_env::hash_encoded::<Sha2x256, _>(&data, &mut output);
-        Hash::from(output)
+        let mut input = [LEAF_PREFIX; 17];
+        input[1..].copy_from_slice(&data.to_le_bytes()); // SCALE encoding
+        sha256(&input)
+    }
+
+    // Helper to calculate the hash of an empty leaf.
+    fn empty_hash() -> Hash {
+        sha256(&[LEAF_PREFIX])
     }
 
     // Helper to concatenate two hashes.
     fn concat_hash(h1: &Hash, h2: &Hash) -> Hash {
+        let mut input = [NODE_PREFIX; 65];
+        input[1..33].copy_from_slice(h1.as_ref());
+        input[33..].copy_from_slice(h2.as_ref());
+        sha256(&input)
+    }
+
+    // Helper to calculate the SHA-256 hash of input.
+    fn sha256(input: &[u8]) -> Hash {
         let mut output = <Sha2x256 as HashOutput>::Type::default(); // 256-bit buffer
-        ink_env::hash_encoded::<Sha2x256, _>(h1, &mut output);
-        ink_env::hash_encoded::<Sha2x256, _>(h2, &mut output);
+        ink_env::hash_bytes::<Sha2x256>(input, &mut output);
         Hash::from(output)
     }
 
@@ -152,683 +483,512 @@ mod merkle {
         /// Imports all the definitions from the outer scope so we can use them here.
         use super::*;
 
-        #[test]
-        fn test_merkle_depth_0() -> Result<(), String> {
-            let mut mt = MerkleTree::new(
-                0,
+        #[ink::test]
+        fn test_merkle_depth_31() {
+            let mut mt = MerkleTree::new(31);
+
+            assert_eq!(mt.capacity(), 1 << 31);
+            assert_eq!(mt.zeros.len(), 32);
+
+            mt.add_data(Balance::from(10u128)).unwrap();
+            let proof = mt.proof(0).unwrap();
+            assert_eq!(proof.siblings.len(), 31);
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(10u128), 0, proof.siblings),
+                Ok(true)
+            );
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "depth must be at most 31")]
+        fn test_merkle_depth_32() {
+            MerkleTree::new(32);
+        }
+
+        #[ink::test]
+        fn test_merkle_depth_0() {
+            let mut mt = MerkleTree::new(0);
+
+            assert_eq!(mt.add_data(Balance::from(10u128)), Ok(()));
+            assert_eq!(mt.add_data(Balance::from(20u128)), Err(Error::TreeFull));
+
+            assert_eq!(
+                mt.root(),
                 Hash::from([
-                    58, 234, 225, 192, 108, 62, 238, 181, 193, 43, 0, 221, 254, 40, 233, 54, 206,
-                    236, 166, 231, 53, 178, 117, 145, 95, 227, 56, 30, 28, 157, 239, 79,
-                ]),
+                    103, 183, 92, 161, 65, 207, 111, 163, 16, 117, 246, 136, 5, 25, 155, 201, 187,
+                    191, 37, 160, 252, 115, 91, 73, 69, 206, 253, 173, 189, 154, 253, 208,
+                ])
             );
+        }
+
+        #[ink::test]
+        fn test_merkle_root_history() {
+            let mut mt = MerkleTree::with_root_history(0, 1);
+            let empty_root = mt.root();
 
-            mt.add_data(Balance::from(10u128));
+            assert!(mt.is_known_root(empty_root));
+            // an empty leaf does not hash like a zero value
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(0u128), 0, ProofVec::new()),
+                Ok(false)
+            );
 
-            assert_eq!(mt.tree[0][0], mt.root_hash);
+            mt.add_data(Balance::from(10u128)).unwrap();
 
-            Ok(())
+            assert!(!mt.is_known_root(empty_root));
+            assert!(mt.is_known_root(calculate_hash(Balance::from(10u128))));
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(10u128), 0, ProofVec::new()),
+                Ok(true)
+            );
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(0u128), 0, ProofVec::new()),
+                Ok(false)
+            );
+        }
+
+        #[ink::test]
+        fn test_merkle_update_leaf() {
+            let mut mt = MerkleTree::new(0);
+
+            assert_eq!(
+                mt.update_leaf(0, Balance::from(20u128)),
+                Err(Error::IndexOutOfBounds)
+            );
+
+            mt.add_data(Balance::from(10u128)).unwrap();
+            let old_root = mt.root();
+
+            assert_eq!(
+                mt.update_leaf(0, Balance::from(20u128)),
+                Ok((old_root, calculate_hash(Balance::from(20u128))))
+            );
+            assert_eq!(mt.data[0], Some(Balance::from(20u128)));
+            assert!(mt.is_known_root(old_root));
+
+            assert_eq!(
+                mt.clear_leaf(0),
+                Ok((calculate_hash(Balance::from(20u128)), empty_hash()))
+            );
+        }
+
+        #[ink::test]
+        fn test_merkle_clear_leaf() {
+            let mut mt = MerkleTree::new(1);
+            mt.add_data(Balance::from(10u128)).unwrap();
+            mt.add_data(Balance::from(20u128)).unwrap();
+
+            mt.clear_leaf(0).unwrap();
+
+            assert_eq!(mt.leaf(0), Err(Error::LeafCleared));
+            assert_eq!(mt.leaf(1), Ok(Balance::from(20u128)));
+            assert_eq!(mt.leaf(2), Err(Error::IndexOutOfBounds));
+
+            // the cleared leaf is empty, not a zero value
+            let proof = mt.proof(0).unwrap();
+            assert_eq!(proof.leaf, empty_hash());
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(0u128), 0, proof.siblings.clone()),
+                Ok(false)
+            );
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(10u128), 0, proof.siblings.clone()),
+                Ok(false)
+            );
+            assert_eq!(mt.verify_proof(empty_hash(), 0, proof.siblings), Ok(true));
+
+            // a cleared leaf can be set again
+            mt.update_leaf(0, Balance::from(30u128)).unwrap();
+            assert_eq!(mt.leaf(0), Ok(Balance::from(30u128)));
+        }
+
+        #[ink::test]
+        fn test_merkle_verify_proof() {
+            let mut mt = MerkleTree::with_root_history(0, 2);
+            let leaf = calculate_hash(Balance::from(10u128));
+
+            assert_eq!(mt.verify_proof(leaf, 0, ProofVec::new()), Ok(false));
+
+            mt.add_data(Balance::from(10u128)).unwrap();
+
+            assert_eq!(mt.verify_proof(leaf, 0, ProofVec::new()), Ok(true));
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(10u128), 0, ProofVec::new()),
+                Ok(true)
+            );
+            assert_eq!(
+                mt.verify_proof_data(Balance::from(20u128), 0, ProofVec::new()),
+                Ok(false)
+            );
+            assert_eq!(
+                mt.verify_proof(leaf, 1, ProofVec::new()),
+                Err(Error::InvalidProof)
+            );
+            assert_eq!(
+                mt.verify_proof(leaf, 0, vec![leaf]),
+                Err(Error::InvalidProof)
+            );
+
+            // the previous root is still accepted
+            mt.update_leaf(0, Balance::from(20u128)).unwrap();
+            assert_eq!(mt.verify_proof(leaf, 0, ProofVec::new()), Ok(true));
         }
 
-        // #[test]
-        // fn test_merkle_depth_1() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         1,
-        //         Hash::from([
-        //             64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215, 242, 35,
-        //             178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221, 153, 65,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-
-        //     assert_eq!(mt.tree[0][0], mt.root_hash);
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_depth_2() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         2,
-        //         Hash::from([
-        //             254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55, 59, 119,
-        //             125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245, 83,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-        //     mt.add_data(&String::from("baz"));
-        //     mt.add_data(&String::from("yup"));
-
-        //     assert_eq!(mt.tree[0][0], mt.root_hash);
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_depth_3() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         3,
-        //         Hash::from([
-        //             112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148, 21, 181,
-        //             190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117, 124,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-        //     mt.add_data(&String::from("baz"));
-        //     mt.add_data(&String::from("yup"));
-        //     mt.add_data(&String::from("maw"));
-        //     mt.add_data(&String::from("wap"));
-        //     mt.add_data(&String::from("pit"));
-        //     mt.add_data(&String::from("fos"));
-
-        //     assert_eq!(mt.tree[0][0], mt.root_hash);
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_proof_depth_1() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         1,
-        //         Hash::from([
-        //             64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215, 242, 35,
-        //             178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221, 153, 65,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-
-        //     assert_eq!(
-        //         mt.generate_proof(0),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215,
-        //                     242, 35, 178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221,
-        //                     153, 65
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     63, 100, 101, 96, 218, 178, 202, 69, 181, 2, 212, 237, 74, 91, 109,
-        //                     209, 179, 244, 254, 134, 222, 147, 100, 233, 81, 164, 1, 97, 63, 215,
-        //                     21, 76
-        //                 ]),
-        //                 true
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(1),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215,
-        //                     242, 35, 178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221,
-        //                     153, 65
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     118, 180, 157, 7, 191, 196, 18, 209, 75, 222, 7, 179, 69, 87, 179, 113,
-        //                     14, 3, 112, 123, 66, 146, 83, 250, 146, 146, 122, 28, 178, 156, 155,
-        //                     124
-        //                 ]),
-        //                 false
-        //             )
-        //         ]
-        //     );
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_proof_depth_2() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         2,
-        //         Hash::from([
-        //             254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55, 59, 119,
-        //             125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245, 83,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-        //     mt.add_data(&String::from("baz"));
-        //     mt.add_data(&String::from("yup"));
-
-        //     assert_eq!(
-        //         mt.generate_proof(0),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     135, 79, 28, 146, 14, 207, 62, 2, 4, 90, 44, 192, 10, 131, 2, 170, 117,
-        //                     50, 86, 185, 145, 144, 75, 5, 179, 238, 212, 167, 40, 71, 190, 158
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     63, 100, 101, 96, 218, 178, 202, 69, 181, 2, 212, 237, 74, 91, 109,
-        //                     209, 179, 244, 254, 134, 222, 147, 100, 233, 81, 164, 1, 97, 63, 215,
-        //                     21, 76
-        //                 ]),
-        //                 true
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(1),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     135, 79, 28, 146, 14, 207, 62, 2, 4, 90, 44, 192, 10, 131, 2, 170, 117,
-        //                     50, 86, 185, 145, 144, 75, 5, 179, 238, 212, 167, 40, 71, 190, 158
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     118, 180, 157, 7, 191, 196, 18, 209, 75, 222, 7, 179, 69, 87, 179, 113,
-        //                     14, 3, 112, 123, 66, 146, 83, 250, 146, 146, 122, 28, 178, 156, 155,
-        //                     124
-        //                 ]),
-        //                 false
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(2),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215,
-        //                     242, 35, 178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221,
-        //                     153, 65
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     133, 8, 186, 149, 254, 122, 181, 59, 180, 27, 104, 39, 157, 47, 94,
-        //                     220, 218, 45, 213, 77, 175, 23, 162, 14, 166, 244, 7, 220, 22, 40, 192,
-        //                     124
-        //                 ]),
-        //                 true
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(3),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215,
-        //                     242, 35, 178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221,
-        //                     153, 65
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     155, 81, 107, 153, 57, 165, 157, 11, 205, 73, 45, 210, 180, 254, 24,
-        //                     181, 135, 101, 92, 17, 108, 170, 185, 170, 117, 148, 188, 30, 20, 34,
-        //                     16, 125
-        //                 ]),
-        //                 false
-        //             )
-        //         ]
-        //     );
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_proof_depth_3() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         3,
-        //         Hash::from([
-        //             112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148, 21, 181,
-        //             190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117, 124,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-        //     mt.add_data(&String::from("baz"));
-        //     mt.add_data(&String::from("yup"));
-        //     mt.add_data(&String::from("maw"));
-        //     mt.add_data(&String::from("wap"));
-        //     mt.add_data(&String::from("pit"));
-        //     mt.add_data(&String::from("fos"));
-
-        //     println!("{:#?}", mt);
-
-        //     assert_eq!(
-        //         mt.generate_proof(0),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     229, 68, 83, 153, 144, 156, 20, 222, 214, 217, 113, 124, 47, 76, 134,
-        //                     95, 112, 56, 117, 26, 215, 229, 22, 196, 206, 103, 219, 253, 26, 221,
-        //                     242, 230
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     135, 79, 28, 146, 14, 207, 62, 2, 4, 90, 44, 192, 10, 131, 2, 170, 117,
-        //                     50, 86, 185, 145, 144, 75, 5, 179, 238, 212, 167, 40, 71, 190, 158
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     63, 100, 101, 96, 218, 178, 202, 69, 181, 2, 212, 237, 74, 91, 109,
-        //                     209, 179, 244, 254, 134, 222, 147, 100, 233, 81, 164, 1, 97, 63, 215,
-        //                     21, 76
-        //                 ]),
-        //                 true
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(1),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     229, 68, 83, 153, 144, 156, 20, 222, 214, 217, 113, 124, 47, 76, 134,
-        //                     95, 112, 56, 117, 26, 215, 229, 22, 196, 206, 103, 219, 253, 26, 221,
-        //                     242, 230
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     135, 79, 28, 146, 14, 207, 62, 2, 4, 90, 44, 192, 10, 131, 2, 170, 117,
-        //                     50, 86, 185, 145, 144, 75, 5, 179, 238, 212, 167, 40, 71, 190, 158
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     118, 180, 157, 7, 191, 196, 18, 209, 75, 222, 7, 179, 69, 87, 179, 113,
-        //                     14, 3, 112, 123, 66, 146, 83, 250, 146, 146, 122, 28, 178, 156, 155,
-        //                     124
-        //                 ]),
-        //                 false
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(2),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     229, 68, 83, 153, 144, 156, 20, 222, 214, 217, 113, 124, 47, 76, 134,
-        //                     95, 112, 56, 117, 26, 215, 229, 22, 196, 206, 103, 219, 253, 26, 221,
-        //                     242, 230
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215,
-        //                     242, 35, 178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221,
-        //                     153, 65
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     133, 8, 186, 149, 254, 122, 181, 59, 180, 27, 104, 39, 157, 47, 94,
-        //                     220, 218, 45, 213, 77, 175, 23, 162, 14, 166, 244, 7, 220, 22, 40, 192,
-        //                     124
-        //                 ]),
-        //                 true
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(3),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     229, 68, 83, 153, 144, 156, 20, 222, 214, 217, 113, 124, 47, 76, 134,
-        //                     95, 112, 56, 117, 26, 215, 229, 22, 196, 206, 103, 219, 253, 26, 221,
-        //                     242, 230
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215,
-        //                     242, 35, 178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221,
-        //                     153, 65
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     155, 81, 107, 153, 57, 165, 157, 11, 205, 73, 45, 210, 180, 254, 24,
-        //                     181, 135, 101, 92, 17, 108, 170, 185, 170, 117, 148, 188, 30, 20, 34,
-        //                     16, 125
-        //                 ]),
-        //                 false
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(4),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     140, 206, 71, 81, 207, 138, 152, 233, 136, 163, 36, 217, 62, 66, 198,
-        //                     33, 184, 126, 89, 6, 37, 118, 204, 199, 98, 208, 64, 247, 39, 235, 34,
-        //                     174
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     85, 114, 5, 36, 70, 244, 220, 223, 134, 177, 233, 45, 191, 186, 137,
-        //                     181, 147, 210, 213, 223, 37, 200, 175, 101, 36, 8, 228, 120, 39, 171,
-        //                     103, 59
-        //                 ]),
-        //                 true
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(5),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     140, 206, 71, 81, 207, 138, 152, 233, 136, 163, 36, 217, 62, 66, 198,
-        //                     33, 184, 126, 89, 6, 37, 118, 204, 199, 98, 208, 64, 247, 39, 235, 34,
-        //                     174
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     138, 150, 65, 116, 174, 72, 169, 234, 89, 107, 224, 179, 152, 162, 114,
-        //                     189, 203, 255, 52, 203, 121, 125, 196, 40, 88, 93, 126, 252, 2, 62, 27,
-        //                     196
-        //                 ]),
-        //                 false
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(6),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     42, 146, 158, 253, 90, 152, 165, 44, 153, 192, 65, 110, 211, 164, 183,
-        //                     17, 51, 156, 13, 20, 200, 59, 61, 98, 4, 177, 153, 131, 83, 147, 109,
-        //                     133
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     150, 40, 244, 219, 122, 25, 223, 75, 239, 193, 60, 84, 103, 37, 105,
-        //                     27, 217, 9, 155, 141, 174, 244, 183, 113, 17, 26, 6, 236, 10, 50, 89,
-        //                     174
-        //                 ]),
-        //                 true
-        //             )
-        //         ]
-        //     );
-        //     assert_eq!(
-        //         mt.generate_proof(7),
-        //         [
-        //             (
-        //                 Hash::from([
-        //                     112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148,
-        //                     21, 181, 190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117,
-        //                     124
-        //                 ]),
-        //                 true
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55,
-        //                     59, 119, 125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245,
-        //                     83
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     42, 146, 158, 253, 90, 152, 165, 44, 153, 192, 65, 110, 211, 164, 183,
-        //                     17, 51, 156, 13, 20, 200, 59, 61, 98, 4, 177, 153, 131, 83, 147, 109,
-        //                     133
-        //                 ]),
-        //                 false
-        //             ),
-        //             (
-        //                 Hash::from([
-        //                     228, 188, 61, 70, 88, 133, 247, 129, 164, 203, 208, 77, 24, 254, 165,
-        //                     165, 237, 16, 209, 177, 74, 104, 151, 13, 152, 167, 151, 45, 59, 232,
-        //                     134, 155
-        //                 ]),
-        //                 false
-        //             )
-        //         ]
-        //     );
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_verify_depth_1() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         1,
-        //         Hash::from([
-        //             64, 13, 167, 79, 119, 176, 55, 201, 53, 122, 13, 245, 64, 121, 215, 242, 35,
-        //             178, 62, 214, 111, 112, 79, 0, 63, 171, 165, 102, 122, 221, 153, 65,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-
-        //     assert_eq!(mt.verify(&String::from("foo"), 0), true);
-        //     assert_eq!(mt.verify(&String::from("bar"), 1), true);
-        //     assert_eq!(mt.verify(&String::from("bar"), 0), false);
-        //     assert_eq!(mt.verify(&String::from("foo"), 1), false);
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_verify_depth_2() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         2,
-        //         Hash::from([
-        //             254, 193, 153, 90, 96, 71, 91, 12, 93, 104, 47, 6, 229, 251, 40, 55, 59, 119,
-        //             125, 90, 25, 131, 131, 98, 183, 113, 85, 28, 187, 165, 245, 83,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-        //     mt.add_data(&String::from("baz"));
-        //     mt.add_data(&String::from("yup"));
-
-        //     assert_eq!(mt.verify(&String::from("foo"), 0), true);
-        //     assert_eq!(mt.verify(&String::from("bar"), 1), true);
-        //     assert_eq!(mt.verify(&String::from("baz"), 2), true);
-        //     assert_eq!(mt.verify(&String::from("yup"), 3), true);
-        //     assert_eq!(mt.verify(&String::from("bar"), 0), false);
-        //     assert_eq!(mt.verify(&String::from("baz"), 1), false);
-        //     assert_eq!(mt.verify(&String::from("yup"), 2), false);
-        //     assert_eq!(mt.verify(&String::from("foo"), 3), false);
-
-        //     Ok(())
-        // }
-
-        // #[test]
-        // fn test_merkle_verify_depth_3() -> Result<(), String> {
-        //     let mut mt = MerkleTree::new(
-        //         3,
-        //         Hash::from([
-        //             112, 27, 168, 163, 68, 164, 18, 124, 211, 124, 119, 75, 52, 220, 148, 21, 181,
-        //             190, 193, 125, 109, 13, 250, 251, 234, 62, 56, 9, 195, 6, 117, 124,
-        //         ]),
-        //     );
-
-        //     mt.add_data(&String::from("foo"));
-        //     mt.add_data(&String::from("bar"));
-        //     mt.add_data(&String::from("baz"));
-        //     mt.add_data(&String::from("yup"));
-        //     mt.add_data(&String::from("maw"));
-        //     mt.add_data(&String::from("wap"));
-        //     mt.add_data(&String::from("pit"));
-        //     mt.add_data(&String::from("fos"));
-
-        //     println!("{:#?}", mt);
-
-        //     assert_eq!(mt.verify(&String::from("foo"), 0), true);
-        //     assert_eq!(mt.verify(&String::from("bar"), 1), true);
-        //     assert_eq!(mt.verify(&String::from("baz"), 2), true);
-        //     assert_eq!(mt.verify(&String::from("yup"), 3), true);
-        //     assert_eq!(mt.verify(&String::from("maw"), 4), true);
-        //     assert_eq!(mt.verify(&String::from("wap"), 5), true);
-        //     assert_eq!(mt.verify(&String::from("pit"), 6), true);
-        //     assert_eq!(mt.verify(&String::from("fos"), 7), true);
-        //     assert_eq!(mt.verify(&String::from("bar"), 0), false);
-        //     assert_eq!(mt.verify(&String::from("baz"), 1), false);
-        //     assert_eq!(mt.verify(&String::from("yup"), 2), false);
-        //     assert_eq!(mt.verify(&String::from("maw"), 3), false);
-        //     assert_eq!(mt.verify(&String::from("wap"), 4), false);
-        //     assert_eq!(mt.verify(&String::from("pit"), 5), false);
-        //     assert_eq!(mt.verify(&String::from("fos"), 6), false);
-        //     assert_eq!(mt.verify(&String::from("foo"), 7), false);
-
-        //     Ok(())
-        // }
+        #[ink::test]
+        fn test_merkle_queries() {
+            let mut mt = MerkleTree::new(0);
+            let empty_root = empty_hash();
+
+            assert_eq!(mt.root(), empty_root);
+            assert_eq!(mt.depth(), 0);
+            assert_eq!(mt.len(), 0);
+            assert_eq!(mt.capacity(), 1);
+            assert_eq!(mt.leaf(0), Err(Error::IndexOutOfBounds));
+            assert_eq!(mt.proof(0), Err(Error::IndexOutOfBounds));
+
+            mt.add_data(Balance::from(10u128)).unwrap();
+            let leaf = calculate_hash(Balance::from(10u128));
+
+            assert_eq!(mt.root(), leaf);
+            assert_eq!(mt.len(), 1);
+            assert_eq!(mt.leaf(0), Ok(Balance::from(10u128)));
+            assert_eq!(mt.node(0, 0), Ok(leaf));
+            assert_eq!(mt.node(0, 1), Err(Error::IndexOutOfBounds));
+            assert_eq!(mt.node(1, 0), Err(Error::IndexOutOfBounds));
+
+            let proof = mt.proof(0).unwrap();
+            assert_eq!(
+                proof,
+                Proof {
+                    leaf_index: 0,
+                    leaf,
+                    root: leaf,
+                    siblings: ProofVec::new(),
+                }
+            );
+            assert_eq!(
+                mt.verify_proof(proof.leaf, proof.leaf_index, proof.siblings),
+                Ok(true)
+            );
+        }
+
+        #[ink::test]
+        fn test_merkle_unauthorized() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+                .expect("cannot get accounts");
+            let mut mt = MerkleTree::new(0);
+            assert_eq!(mt.owner(), accounts.alice);
+
+            // call as bob
+            let callee =
+                ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap_or([0x0; 32].into());
+            let data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+                accounts.bob,
+                callee,
+                1000000,
+                1000000,
+                data,
+            );
+
+            assert_eq!(mt.add_data(Balance::from(10u128)), Err(Error::Unauthorized));
+            assert_eq!(
+                mt.update_leaf(0, Balance::from(10u128)),
+                Err(Error::Unauthorized)
+            );
+            assert_eq!(mt.len(), 0);
+        }
+
+        #[ink::test]
+        fn test_merkle_events() {
+            let mut mt = MerkleTree::new(0);
+            let empty_root = mt.root();
+            let leaf_hash = calculate_hash(Balance::from(10u128));
+
+            mt.add_data(Balance::from(10u128)).unwrap();
+            mt.update_leaf(0, Balance::from(20u128)).unwrap();
+
+            let events = ink_env::test::recorded_events()
+                .map(|e| <Event as scale::Decode>::decode(&mut &e.data[..]).unwrap())
+                .collect::<ProofVec<_>>();
+            assert_eq!(events.len(), 5);
+
+            match &events[0] {
+                Event::LeafInserted(e) => {
+                    assert_eq!(e.index, 0);
+                    assert_eq!(e.leaf_hash, leaf_hash);
+                    assert_eq!(e.data, Balance::from(10u128));
+                }
+                _ => panic!("expected LeafInserted"),
+            }
+            match &events[1] {
+                Event::RootUpdated(e) => {
+                    assert_eq!(e.old_root, empty_root);
+                    assert_eq!(e.new_root, leaf_hash);
+                    assert_eq!(e.size, 1);
+                }
+                _ => panic!("expected RootUpdated"),
+            }
+            match &events[2] {
+                Event::TreeFull(e) => assert_eq!(e.capacity, 1),
+                _ => panic!("expected TreeFull"),
+            }
+            match &events[3] {
+                Event::LeafUpdated(e) => {
+                    assert_eq!(e.index, 0);
+                    assert_eq!(e.data, Some(Balance::from(20u128)));
+                }
+                _ => panic!("expected LeafUpdated"),
+            }
+            match &events[4] {
+                Event::RootUpdated(e) => {
+                    assert_eq!(e.old_root, leaf_hash);
+                    assert_eq!(e.new_root, mt.root());
+                }
+                _ => panic!("expected RootUpdated"),
+            }
+
+            // a rejected insertion changes nothing
+            assert_eq!(mt.add_data(Balance::from(30u128)), Err(Error::TreeFull));
+            assert_eq!(ink_env::test::recorded_events().count(), 5);
+        }
+
+        /// Fills the tree with the values 10, 20, 30, ...
+        fn fill(mt: &mut MerkleTree) {
+            for k in mt.len()..mt.capacity() {
+                mt.add_data(Balance::from(10 * (k as u128 + 1))).unwrap();
+            }
+        }
+
+        /// Returns the hashes of the leaves `fill` adds.
+        fn leaves(n: u32) -> ProofVec<Hash> {
+            (0..n)
+                .map(|k| calculate_hash(Balance::from(10 * (k as u128 + 1))))
+                .collect()
+        }
+
+        /// Checks data against the proof the tree generates for index.
+        fn verify(mt: &MerkleTree, data: u128, index: u32) -> bool {
+            let proof = mt.proof(index).unwrap();
+            mt.verify_proof_data(Balance::from(data), index, proof.siblings)
+                .unwrap()
+        }
+
+        // The nodes on level 1 and 2 of a tree of depth 3 holding 10, 20,
+        // ..., 80, as computed by `MerkleTree::<u128, Sha256>` of the
+        // off-chain library. A tree of depth 1 holding 10 and 20 has root
+        // LEVEL_1[0], and one of depth 2 holding 10, ..., 40 has root
+        // LEVEL_2[0].
+        const LEVEL_1: [[u8; 32]; 4] = [
+            [
+                95, 65, 131, 51, 63, 149, 162, 240, 41, 195, 80, 125, 49, 229, 142, 182, 161, 128,
+                11, 147, 0, 19, 70, 241, 60, 133, 71, 228, 58, 18, 139, 178,
+            ],
+            [
+                60, 150, 217, 238, 56, 207, 9, 9, 255, 34, 60, 81, 23, 163, 122, 125, 130, 235, 5,
+                14, 175, 47, 242, 19, 160, 54, 143, 231, 221, 236, 71, 243,
+            ],
+            [
+                162, 40, 119, 188, 249, 53, 18, 254, 38, 211, 168, 228, 149, 175, 36, 184, 223,
+                178, 2, 194, 239, 222, 10, 1, 238, 241, 169, 85, 186, 44, 99, 199,
+            ],
+            [
+                44, 109, 142, 123, 76, 27, 127, 210, 92, 142, 217, 139, 240, 86, 25, 158, 82, 2,
+                253, 63, 140, 225, 77, 144, 99, 126, 206, 190, 27, 184, 255, 72,
+            ],
+        ];
+        const LEVEL_2: [[u8; 32]; 2] = [
+            [
+                188, 8, 213, 193, 71, 243, 185, 171, 196, 148, 149, 104, 0, 121, 202, 68, 6, 19,
+                247, 38, 47, 33, 41, 60, 78, 120, 204, 109, 246, 85, 119, 33,
+            ],
+            [
+                19, 132, 214, 178, 211, 255, 158, 74, 199, 164, 166, 87, 215, 47, 122, 190, 237,
+                215, 251, 168, 59, 197, 8, 193, 14, 94, 224, 79, 45, 195, 171, 222,
+            ],
+        ];
+
+        #[ink::test]
+        fn test_merkle_depth_1() {
+            let mut mt = MerkleTree::new(1);
+            assert_eq!(
+                mt.root(),
+                Hash::from([
+                    254, 67, 214, 106, 250, 74, 154, 92, 79, 156, 157, 168, 159, 79, 251, 82, 99,
+                    92, 143, 52, 46, 127, 251, 115, 29, 104, 227, 108, 89, 130, 7, 42
+                ])
+            );
+
+            fill(&mut mt);
+
+            assert_eq!(mt.root(), Hash::from(LEVEL_1[0]));
+        }
+
+        #[ink::test]
+        fn test_merkle_depth_2() {
+            let mut mt = MerkleTree::new(2);
+            assert_eq!(
+                mt.root(),
+                Hash::from([
+                    222, 184, 46, 21, 89, 84, 214, 190, 20, 89, 44, 102, 204, 247, 161, 236, 225,
+                    147, 238, 235, 205, 171, 175, 116, 123, 145, 244, 69, 25, 240, 159, 71
+                ])
+            );
+
+            mt.add_data(Balance::from(10u128)).unwrap();
+            mt.add_data(Balance::from(20u128)).unwrap();
+            mt.add_data(Balance::from(30u128)).unwrap();
+            assert_eq!(
+                mt.root(),
+                Hash::from([
+                    48, 127, 51, 214, 158, 224, 46, 189, 107, 115, 72, 103, 70, 207, 52, 112, 210,
+                    68, 189, 75, 56, 233, 164, 51, 57, 82, 125, 147, 35, 220, 78, 29
+                ])
+            );
+
+            fill(&mut mt);
+
+            assert_eq!(mt.root(), Hash::from(LEVEL_2[0]));
+        }
+
+        #[ink::test]
+        fn test_merkle_depth_3() {
+            let mut mt = MerkleTree::new(3);
+
+            // construction only stores the empty subtree of every height
+            assert_eq!(mt.zeros.len(), 4);
+            assert!(mt.tree.iter().all(|row| row.is_empty()));
+            assert_eq!(
+                mt.root(),
+                Hash::from([
+                    41, 96, 4, 76, 98, 242, 53, 78, 148, 94, 141, 120, 253, 210, 32, 160, 95, 44,
+                    8, 121, 242, 77, 246, 241, 30, 245, 204, 38, 181, 39, 10, 14
+                ])
+            );
+
+            mt.add_data(Balance::from(10u128)).unwrap();
+            mt.add_data(Balance::from(20u128)).unwrap();
+            mt.add_data(Balance::from(30u128)).unwrap();
+            assert_eq!(mt.tree[3].len(), 3);
+            assert_eq!(
+                mt.root(),
+                Hash::from([
+                    159, 179, 203, 22, 31, 37, 251, 243, 153, 161, 204, 56, 52, 185, 43, 124, 171,
+                    6, 112, 202, 183, 189, 32, 244, 7, 42, 183, 155, 194, 237, 122, 108
+                ])
+            );
+
+            fill(&mut mt);
+
+            assert_eq!(
+                mt.root(),
+                Hash::from([
+                    131, 181, 132, 102, 155, 230, 31, 70, 223, 178, 44, 171, 233, 41, 35, 1, 231,
+                    33, 43, 71, 28, 245, 185, 232, 128, 126, 226, 202, 6, 195, 35, 187,
+                ])
+            );
+            assert_eq!(
+                mt.root(),
+                concat_hash(&Hash::from(LEVEL_2[0]), &Hash::from(LEVEL_2[1]))
+            );
+        }
+
+        #[ink::test]
+        fn test_merkle_proof_depth_1() {
+            let mut mt = MerkleTree::new(1);
+            fill(&mut mt);
+            let leaves = leaves(2);
+
+            assert_eq!(mt.generate_proof(0), Ok(vec![leaves[1]]));
+            assert_eq!(mt.generate_proof(1), Ok(vec![leaves[0]]));
+            assert_eq!(mt.generate_proof(2), Err(Error::IndexOutOfBounds));
+        }
+
+        #[ink::test]
+        fn test_merkle_proof_depth_2() {
+            let mut mt = MerkleTree::new(2);
+            fill(&mut mt);
+            let leaves = leaves(4);
+
+            assert_eq!(
+                mt.generate_proof(0),
+                Ok(vec![leaves[1], Hash::from(LEVEL_1[1])])
+            );
+            assert_eq!(
+                mt.generate_proof(1),
+                Ok(vec![leaves[0], Hash::from(LEVEL_1[1])])
+            );
+            assert_eq!(
+                mt.generate_proof(2),
+                Ok(vec![leaves[3], Hash::from(LEVEL_1[0])])
+            );
+            assert_eq!(
+                mt.generate_proof(3),
+                Ok(vec![leaves[2], Hash::from(LEVEL_1[0])])
+            );
+        }
+
+        #[ink::test]
+        fn test_merkle_proof_depth_3() {
+            let mut mt = MerkleTree::new(3);
+            fill(&mut mt);
+            let leaves = leaves(8);
+
+            for i in 0..8 {
+                assert_eq!(
+                    mt.generate_proof(i),
+                    Ok(vec![
+                        leaves[(i ^ 1) as usize],
+                        Hash::from(LEVEL_1[(i / 2 ^ 1) as usize]),
+                        Hash::from(LEVEL_2[(i / 4 ^ 1) as usize]),
+                    ])
+                );
+            }
+        }
+
+        #[ink::test]
+        fn test_merkle_verify_depth_1() {
+            let mut mt = MerkleTree::new(1);
+            fill(&mut mt);
+
+            assert!(verify(&mt, 10, 0));
+            assert!(verify(&mt, 20, 1));
+            assert!(!verify(&mt, 20, 0));
+            assert!(!verify(&mt, 10, 1));
+        }
+
+        #[ink::test]
+        fn test_merkle_verify_depth_2() {
+            let mut mt = MerkleTree::new(2);
+            fill(&mut mt);
+
+            assert!(verify(&mt, 10, 0));
+            assert!(verify(&mt, 20, 1));
+            assert!(verify(&mt, 30, 2));
+            assert!(verify(&mt, 40, 3));
+            assert!(!verify(&mt, 20, 0));
+            assert!(!verify(&mt, 30, 1));
+            assert!(!verify(&mt, 40, 2));
+            assert!(!verify(&mt, 10, 3));
+        }
+
+        #[ink::test]
+        fn test_merkle_verify_depth_3() {
+            let mut mt = MerkleTree::with_root_history(3, 1);
+
+            // proofs of a partially filled tree include empty subtrees
+            mt.add_data(Balance::from(10u128)).unwrap();
+            mt.add_data(Balance::from(20u128)).unwrap();
+            mt.add_data(Balance::from(30u128)).unwrap();
+            assert!(verify(&mt, 30, 2));
+            assert!(!verify(&mt, 20, 2));
+
+            fill(&mut mt);
+
+            for k in 0..8 {
+                assert!(verify(&mt, 10 * (k as u128 + 1), k));
+                assert!(!verify(&mt, 10 * (k as u128 + 1), (k + 1) % 8));
+            }
+        }
     }
 }
@@ -0,0 +1,78 @@
+//! Errors reported by the tree.
+
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+
+/// Errors returned by fallible tree operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleError {
+    /// The tree already holds as many leaves as it can.
+    TreeFull,
+    /// No leaf has been added at `index`; the tree holds `len` leaves.
+    IndexOutOfRange { index: usize, len: usize },
+    /// The key does not address a leaf of the tree.
+    KeyOutOfRange,
+    /// The key or value is already stored in the tree.
+    KeyExists,
+    /// The key or value is not stored in the tree.
+    KeyNotFound,
+    /// The proof is structurally invalid, e.g. it is empty.
+    MalformedProof,
+    /// The proof was generated for a tree of a different depth.
+    DepthMismatch { expected: usize, actual: usize },
+    /// The proof was generated with a different hash function.
+    HasherMismatch,
+    /// A sum of balances does not fit in a `u128`.
+    SumOverflow,
+    /// `start` and `end` are out of order, e.g. an old tree size is larger
+    /// than the new one or a range of leaves is empty.
+    InvalidRange { start: usize, end: usize },
+}
+
+impl Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MerkleError::TreeFull => write!(f, "tree is full"),
+            MerkleError::IndexOutOfRange { index, len } => {
+                write!(f, "index {} out of range for tree of {} leaves", index, len)
+            }
+            MerkleError::KeyOutOfRange => write!(f, "key out of range"),
+            MerkleError::KeyExists => write!(f, "key already exists"),
+            MerkleError::KeyNotFound => write!(f, "key not found"),
+            MerkleError::MalformedProof => write!(f, "malformed proof"),
+            MerkleError::DepthMismatch { expected, actual } => write!(
+                f,
+                "proof depth {} does not match tree depth {}",
+                actual, expected
+            ),
+            MerkleError::HasherMismatch => write!(f, "proof uses a different hasher"),
+            MerkleError::SumOverflow => write!(f, "sum of balances overflows"),
+            MerkleError::InvalidRange { start, end } => {
+                write!(f, "invalid range {}..{}", start, end)
+            }
+        }
+    }
+}
+
+impl Error for MerkleError {}
+
+/// The root computed by a tree differs from the root it was pinned to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RootMismatch<D> {
+    /// The root the tree was pinned to.
+    pub expected: D,
+    /// The root the tree actually computed.
+    pub actual: D,
+}
+
+impl<D: Debug> Display for RootMismatch<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "root mismatch: expected {:?}, computed {:?}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl<D: Debug> Error for RootMismatch<D> {}
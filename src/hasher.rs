@@ -0,0 +1,316 @@
+//! Hash functions a [`MerkleTree`](crate::MerkleTree) can be built with.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::Hasher;
+
+use blake2::digest::consts::U32;
+use blake2::digest::{Digest, OutputSizeUser};
+use blake2::Blake2b;
+
+/// A 256-bit digest as produced by the cryptographic hashers.
+pub type Hash256 = [u8; 32];
+
+/// Byte prepended to leaf data before hashing in [`TreeFormat::V1`].
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// Byte prepended to a pair of child digests before hashing in
+/// [`TreeFormat::V1`].
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// The encoding used to turn leaves and pairs of children into hash input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeFormat {
+    /// The original encoding: leaves are hashed as is and children are
+    /// combined by concatenating their decimal representations. This is
+    /// ambiguous (`12 || 3` and `1 || 23` collide) and does not separate
+    /// leaves from internal nodes.
+    V0,
+    /// RFC 6962 style encoding: a leaf hashes `0x00 || data` and an
+    /// internal node hashes `0x01 || left || right` over the fixed-width
+    /// child digests.
+    V1,
+}
+
+/// Identifies a [`MerkleHasher`] so proofs can record what they were
+/// built with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HasherId {
+    Legacy,
+    Sha256,
+    Keccak256,
+    Blake2b256,
+}
+
+/// The hash function used to derive leaf and internal node hashes.
+pub trait MerkleHasher {
+    /// The output of the hash function.
+    type Digest: Copy + Eq + Debug;
+
+    /// The identifier recorded in proofs.
+    const ID: HasherId;
+
+    /// The encoding of leaves and internal nodes.
+    const FORMAT: TreeFormat;
+
+    /// Hashes the raw bytes of a leaf.
+    fn hash_leaf(data: &[u8]) -> Self::Digest;
+
+    /// Combines the hashes of a left and right child into their parent's hash.
+    fn hash_nodes(left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+
+    /// Hashes empty input, which RFC 6962 defines as the root of a tree
+    /// without leaves.
+    fn hash_empty() -> Self::Digest;
+}
+
+/// SHA-256.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha256;
+
+/// Keccak-256, as used by Ethereum.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Keccak256;
+
+/// BLAKE2b with a 256-bit output.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake2b256;
+
+/// The standard library's `DefaultHasher` hashing decimal strings.
+///
+/// This is what trees were originally built with and the only hasher
+/// using [`TreeFormat::V0`]. Its algorithm is not guaranteed to be stable
+/// across Rust releases and its 64-bit output is not collision resistant,
+/// so it should only be used to verify roots computed by earlier versions
+/// of this crate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LegacyHasher;
+
+fn digest_leaf<D: Digest + OutputSizeUser<OutputSize = U32>>(data: &[u8]) -> Hash256 {
+    D::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+fn digest_empty<D: Digest + OutputSizeUser<OutputSize = U32>>() -> Hash256 {
+    D::new().finalize().into()
+}
+
+fn digest_nodes<D: Digest + OutputSizeUser<OutputSize = U32>>(
+    left: &Hash256,
+    right: &Hash256,
+) -> Hash256 {
+    D::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+impl MerkleHasher for Sha256 {
+    type Digest = Hash256;
+    const ID: HasherId = HasherId::Sha256;
+    const FORMAT: TreeFormat = TreeFormat::V1;
+
+    fn hash_leaf(data: &[u8]) -> Hash256 {
+        digest_leaf::<sha2::Sha256>(data)
+    }
+
+    fn hash_nodes(left: &Hash256, right: &Hash256) -> Hash256 {
+        digest_nodes::<sha2::Sha256>(left, right)
+    }
+
+    fn hash_empty() -> Hash256 {
+        digest_empty::<sha2::Sha256>()
+    }
+}
+
+impl MerkleHasher for Keccak256 {
+    type Digest = Hash256;
+    const ID: HasherId = HasherId::Keccak256;
+    const FORMAT: TreeFormat = TreeFormat::V1;
+
+    fn hash_leaf(data: &[u8]) -> Hash256 {
+        digest_leaf::<sha3::Keccak256>(data)
+    }
+
+    fn hash_nodes(left: &Hash256, right: &Hash256) -> Hash256 {
+        digest_nodes::<sha3::Keccak256>(left, right)
+    }
+
+    fn hash_empty() -> Hash256 {
+        digest_empty::<sha3::Keccak256>()
+    }
+}
+
+impl MerkleHasher for Blake2b256 {
+    type Digest = Hash256;
+    const ID: HasherId = HasherId::Blake2b256;
+    const FORMAT: TreeFormat = TreeFormat::V1;
+
+    fn hash_leaf(data: &[u8]) -> Hash256 {
+        digest_leaf::<Blake2b<U32>>(data)
+    }
+
+    fn hash_nodes(left: &Hash256, right: &Hash256) -> Hash256 {
+        digest_nodes::<Blake2b<U32>>(left, right)
+    }
+
+    fn hash_empty() -> Hash256 {
+        digest_empty::<Blake2b<U32>>()
+    }
+}
+
+/// Returns the roots of empty subtrees of every height up to `height`,
+/// where `zero_hashes[0]` is the hash of an empty leaf and
+/// `zero_hashes[h]` combines two copies of `zero_hashes[h - 1]`.
+pub fn zero_hashes<H: MerkleHasher>(height: usize) -> Vec<H::Digest> {
+    let mut zero = Vec::with_capacity(height + 1);
+    zero.push(H::hash_leaf(b""));
+    for h in 0..height {
+        zero.push(H::hash_nodes(&zero[h], &zero[h]));
+    }
+    zero
+}
+
+impl LegacyHasher {
+    // Hashes bytes the way `str::hash` does, so that hashing the bytes of
+    // a string gives the same result as hashing the string itself.
+    fn hash_str_bytes(data: &[u8]) -> u64 {
+        let mut s = DefaultHasher::new();
+        s.write(data);
+        s.write_u8(0xff);
+        s.finish()
+    }
+}
+
+impl MerkleHasher for LegacyHasher {
+    type Digest = u64;
+    const ID: HasherId = HasherId::Legacy;
+    const FORMAT: TreeFormat = TreeFormat::V0;
+
+    fn hash_leaf(data: &[u8]) -> u64 {
+        LegacyHasher::hash_str_bytes(data)
+    }
+
+    fn hash_nodes(left: &u64, right: &u64) -> u64 {
+        LegacyHasher::hash_str_bytes(format!("{}{}", left, right).as_bytes())
+    }
+
+    fn hash_empty() -> u64 {
+        DefaultHasher::new().finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::Hash;
+
+    fn to_hex(digest: &Hash256) -> String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_legacy_matches_str_hash() -> Result<(), String> {
+        for data in &["", "foo", "bar", "1234567890"] {
+            let mut s = DefaultHasher::new();
+            data.hash(&mut s);
+            assert_eq!(LegacyHasher::hash_leaf(data.as_bytes()), s.finish());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_hashes() -> Result<(), String> {
+        let zero = zero_hashes::<LegacyHasher>(2);
+
+        assert_eq!(zero.len(), 3);
+        assert_eq!(zero[0], LegacyHasher::hash_leaf(b""));
+        assert_eq!(zero[2], LegacyHasher::hash_nodes(&zero[1], &zero[1]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_nodes_are_ambiguous() -> Result<(), String> {
+        assert_eq!(
+            LegacyHasher::hash_nodes(&12, &3),
+            LegacyHasher::hash_nodes(&1, &23)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256_empty() -> Result<(), String> {
+        // RFC 6962 hash of a tree with a single empty leaf
+        assert_eq!(
+            to_hex(&Sha256::hash_leaf(b"")),
+            "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256_no_input() -> Result<(), String> {
+        assert_eq!(
+            to_hex(&Sha256::hash_empty()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256_nodes() -> Result<(), String> {
+        assert_eq!(
+            to_hex(&Sha256::hash_nodes(
+                &Sha256::hash_leaf(b"foo"),
+                &Sha256::hash_leaf(b"bar")
+            )),
+            "39286a4a5531622751d6845bb8efb4cf33bec2c5f3f8430d7584874371a35bda"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_is_not_a_leaf() -> Result<(), String> {
+        let left = Sha256::hash_leaf(b"foo");
+        let right = Sha256::hash_leaf(b"bar");
+        let mut data = left.to_vec();
+        data.extend_from_slice(&right);
+
+        // presenting the children of an internal node as leaf data must
+        // not reproduce the internal node's hash
+        assert_ne!(Sha256::hash_leaf(&data), Sha256::hash_nodes(&left, &right));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keccak256_empty() -> Result<(), String> {
+        assert_eq!(
+            to_hex(&Keccak256::hash_leaf(b"")),
+            "bc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blake2b256_empty() -> Result<(), String> {
+        assert_eq!(
+            to_hex(&Blake2b256::hash_leaf(b"")),
+            "03170a2e7597b7b7e3d84c05391d139a62b157e78786d8c082f29dcf4c111314"
+        );
+
+        Ok(())
+    }
+}
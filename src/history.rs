@@ -0,0 +1,107 @@
+//! A bounded history of the roots a tree has had.
+
+use std::collections::VecDeque;
+
+/// The number of roots kept by default.
+pub const DEFAULT_ROOT_HISTORY_SIZE: usize = 30;
+
+/// A ring buffer of the last roots of a tree, newest last.
+///
+/// Keeping recent roots lets proofs generated a few insertions ago still
+/// be accepted, the way mixer-style deposit trees work.
+#[derive(Clone, Debug)]
+pub struct RootHistory<D> {
+    roots: VecDeque<D>,
+    size: usize,
+}
+
+impl<D: Copy + Eq> RootHistory<D> {
+    /// Creates an empty history keeping up to `size` roots.
+    ///
+    /// Panics if `size` is zero.
+    pub fn new(size: usize) -> RootHistory<D> {
+        assert!(size > 0, "root history must keep at least one root");
+
+        RootHistory {
+            roots: VecDeque::with_capacity(size),
+            size,
+        }
+    }
+
+    /// Records `root` as the newest root, dropping the oldest one if the
+    /// history is full.
+    pub fn push(&mut self, root: D) {
+        if self.roots.len() == self.size {
+            self.roots.pop_front();
+        }
+        self.roots.push_back(root);
+    }
+
+    /// Returns true if `root` is one of the recorded roots.
+    pub fn contains(&self, root: &D) -> bool {
+        self.roots.contains(root)
+    }
+
+    /// Returns the newest root, if any.
+    pub fn latest(&self) -> Option<D> {
+        self.roots.back().copied()
+    }
+
+    /// Returns the recorded roots, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &D> {
+        self.roots.iter()
+    }
+
+    /// Returns the number of recorded roots.
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns true if no roots have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Returns the maximum number of roots kept.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Changes the maximum number of roots kept, dropping the oldest roots
+    /// if there are now too many.
+    ///
+    /// Panics if `size` is zero.
+    pub fn resize(&mut self, size: usize) {
+        assert!(size > 0, "root history must keep at least one root");
+
+        while self.roots.len() > size {
+            self.roots.pop_front();
+        }
+        self.size = size;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer() -> Result<(), String> {
+        let mut history = RootHistory::new(3);
+
+        for root in 0..5 {
+            history.push(root);
+        }
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(history.latest(), Some(4));
+        assert!(history.contains(&2));
+        assert!(!history.contains(&1));
+
+        history.resize(1);
+        assert_eq!(history.iter().copied().collect::<Vec<_>>(), [4]);
+
+        Ok(())
+    }
+}
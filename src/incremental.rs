@@ -0,0 +1,149 @@
+//! An append-only Merkle tree that only keeps its frontier.
+
+use crate::proof::proof_root;
+use crate::{
+    zero_hashes, Leaf, MerkleError, MerkleHasher, MerkleProof, RootHistory, Sha256,
+    DEFAULT_ROOT_HISTORY_SIZE,
+};
+
+/// An append-only Merkle tree storing O(depth) hashes, in the style of the
+/// Ethereum deposit contract.
+///
+/// Only the root of the left-most filled subtree at every height is kept,
+/// together with the hashes of empty subtrees. Leaves can be appended and
+/// the root computed in O(depth), and the roots are the same as those of a
+/// [`MerkleTree`](crate::MerkleTree) of the same depth fed the same leaves.
+/// Proofs cannot be generated since the leaves are not stored, but proofs
+/// built elsewhere can be checked against the recent roots.
+#[derive(Clone, Debug)]
+pub struct IncrementalMerkleTree<H: MerkleHasher = Sha256> {
+    depth: usize,
+    // branch[h] is the root of the last completed subtree of height h
+    // that is still waiting for its right sibling; branch[depth] is the
+    // root once the tree is full
+    branch: Vec<H::Digest>,
+    // zero[h] is the hash of an empty subtree of height h
+    zero: Vec<H::Digest>,
+    history: RootHistory<H::Digest>,
+    index: u64,
+}
+
+impl<H: MerkleHasher> IncrementalMerkleTree<H> {
+    /// Creates an empty tree of the given depth.
+    pub fn new(depth: usize) -> IncrementalMerkleTree<H> {
+        let zero = zero_hashes::<H>(depth);
+        let mut history = RootHistory::new(DEFAULT_ROOT_HISTORY_SIZE);
+        history.push(zero[depth]);
+        IncrementalMerkleTree {
+            depth,
+            branch: zero.clone(),
+            zero,
+            history,
+            index: 0,
+        }
+    }
+
+    /// Appends `data` as the next leaf.
+    pub fn add_data(&mut self, data: &(impl Leaf<H> + ?Sized)) -> Result<(), MerkleError> {
+        if self.is_full() {
+            return Err(MerkleError::TreeFull);
+        }
+
+        self.index += 1;
+        let mut node = data.leaf_hash();
+        let mut size = self.index;
+        let mut h = 0;
+        while h < self.depth {
+            // an odd size means this subtree is now a left child waiting
+            // for its sibling
+            if size % 2 == 1 {
+                break;
+            }
+            node = H::hash_nodes(&self.branch[h], &node);
+            size /= 2;
+            h += 1;
+        }
+        self.branch[h] = node;
+        self.history.push(self.root());
+
+        Ok(())
+    }
+
+    /// Returns the current root of the tree.
+    pub fn root(&self) -> H::Digest {
+        if self.is_full() {
+            return self.branch[self.depth];
+        }
+
+        let mut node = self.zero[0];
+        let mut size = self.index;
+        for h in 0..self.depth {
+            node = if size % 2 == 1 {
+                H::hash_nodes(&self.branch[h], &node)
+            } else {
+                H::hash_nodes(&node, &self.zero[h])
+            };
+            size /= 2;
+        }
+        node
+    }
+
+    /// Checks `proof` for `data` against any of the recent roots of the
+    /// tree.
+    ///
+    /// Fails if the proof was generated for a tree of a different depth
+    /// or is otherwise malformed.
+    pub fn verify_against_any_recent_root(
+        &self,
+        data: &(impl Leaf<H> + ?Sized),
+        proof: &MerkleProof<H::Digest>,
+    ) -> Result<bool, MerkleError> {
+        if proof.depth != self.depth {
+            return Err(MerkleError::DepthMismatch {
+                expected: self.depth,
+                actual: proof.depth,
+            });
+        }
+
+        Ok(self.is_known_root(&proof_root::<H>(data, proof)?))
+    }
+
+    /// Returns true if `root` is the current root or one of the recent
+    /// roots kept in the root history.
+    pub fn is_known_root(&self, root: &H::Digest) -> bool {
+        self.history.contains(root)
+    }
+
+    /// Returns the recent roots of the tree, including the current one.
+    pub fn root_history(&self) -> &RootHistory<H::Digest> {
+        &self.history
+    }
+
+    /// Sets the number of recent roots kept, which defaults to
+    /// [`DEFAULT_ROOT_HISTORY_SIZE`].
+    ///
+    /// Panics if `size` is zero.
+    pub fn set_root_history_size(&mut self, size: usize) {
+        self.history.resize(size);
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves appended so far.
+    pub fn len(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns true if no leaves have been appended.
+    pub fn is_empty(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Returns true if no more leaves can be appended.
+    pub fn is_full(&self) -> bool {
+        self.depth < 64 && self.index == 1 << self.depth
+    }
+}
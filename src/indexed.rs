@@ -0,0 +1,260 @@
+//! A Merkle tree of sorted values that can prove a value is absent.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::{
+    verify_proof, EncodeLeaf, MerkleError, MerkleHasher, MerkleProof, MerkleTree, Sha256, SparseKey,
+};
+
+/// A leaf of an [`IndexedMerkleTree`]: a value and a pointer to the leaf
+/// holding the next larger value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IndexedLeaf {
+    /// The value stored in the leaf.
+    pub value: SparseKey,
+    /// The position of the leaf holding the next larger value, or 0 if
+    /// there is none.
+    pub next_index: usize,
+    /// The next larger value, or zero if there is none.
+    pub next_value: SparseKey,
+}
+
+impl EncodeLeaf for IndexedLeaf {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        let mut bytes = Vec::with_capacity(72);
+        bytes.extend_from_slice(self.value.as_bytes());
+        bytes.extend_from_slice(&(self.next_index as u64).to_le_bytes());
+        bytes.extend_from_slice(self.next_value.as_bytes());
+        Cow::Owned(bytes)
+    }
+}
+
+/// A proof that a value is, or is not, stored in an indexed tree.
+///
+/// For a value that is present `leaf` is the leaf holding it; for one
+/// that is absent it is the low leaf, whose value is the largest one
+/// smaller than the absent value and whose next value is larger.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedMerkleProof<D> {
+    /// The leaf the proof is about.
+    pub leaf: IndexedLeaf,
+    /// The proof that `leaf` is part of the tree.
+    pub proof: MerkleProof<D>,
+}
+
+/// Checks that `value` is stored in an indexed tree whose root is `root`.
+pub fn verify_membership<H: MerkleHasher>(
+    root: &H::Digest,
+    value: impl Into<SparseKey>,
+    proof: &IndexedMerkleProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.leaf.value != value.into() {
+        return Ok(false);
+    }
+
+    verify_proof::<H>(root, &proof.leaf, &proof.proof)
+}
+
+/// Checks that `value` is not stored in an indexed tree whose root is
+/// `root`, i.e. that the low leaf in `proof` skips over it.
+pub fn verify_non_membership<H: MerkleHasher>(
+    root: &H::Digest,
+    value: impl Into<SparseKey>,
+    proof: &IndexedMerkleProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    let value = value.into();
+    let leaf = &proof.leaf;
+    // a next index of 0 means the low leaf holds the largest value
+    if leaf.value >= value || (leaf.next_index != 0 && leaf.next_value <= value) {
+        return Ok(false);
+    }
+
+    verify_proof::<H>(root, leaf, &proof.proof)
+}
+
+/// A Merkle tree of distinct 256-bit values that links every leaf to the
+/// leaf holding the next larger value.
+///
+/// Leaves are appended in insertion order, but the links form a sorted
+/// list starting at leaf 0, which holds the value zero. A value that is
+/// not in the tree falls between the value of exactly one leaf, its low
+/// leaf, and the next value that leaf points to, so proving the low leaf
+/// proves the value is absent. Inserting a value appends its leaf and
+/// updates the low leaf to point to it.
+#[derive(Debug)]
+pub struct IndexedMerkleTree<H: MerkleHasher = Sha256> {
+    tree: MerkleTree<IndexedLeaf, H>,
+    // the position of the leaf holding every value, including zero
+    indices: BTreeMap<SparseKey, usize>,
+}
+
+impl<H: MerkleHasher> IndexedMerkleTree<H> {
+    /// Creates a tree of the given depth holding only the zero value, so
+    /// that up to `2^depth - 1` values can be inserted.
+    pub fn new(depth: usize) -> IndexedMerkleTree<H> {
+        let mut tree = MerkleTree::new(depth);
+        tree.add_data(IndexedLeaf::default()).unwrap();
+
+        let mut indices = BTreeMap::new();
+        indices.insert(SparseKey::default(), 0);
+
+        IndexedMerkleTree { tree, indices }
+    }
+
+    /// Inserts `value` and returns the position of its leaf.
+    ///
+    /// Fails with [`MerkleError::KeyExists`] if the value is already
+    /// stored, which zero always is, or [`MerkleError::TreeFull`] if
+    /// there is no room for another leaf.
+    pub fn insert(&mut self, value: impl Into<SparseKey>) -> Result<usize, MerkleError> {
+        let value = value.into();
+        if self.indices.contains_key(&value) {
+            return Err(MerkleError::KeyExists);
+        }
+        if self.tree.len() == self.tree.capacity() {
+            return Err(MerkleError::TreeFull);
+        }
+
+        let (low_index, low) = self.low_leaf(&value);
+        let index = self.tree.len();
+        self.tree.add_data(IndexedLeaf {
+            value,
+            next_index: low.next_index,
+            next_value: low.next_value,
+        })?;
+        self.tree.update_leaf(
+            low_index,
+            IndexedLeaf {
+                value: low.value,
+                next_index: index,
+                next_value: value,
+            },
+        )?;
+        self.indices.insert(value, index);
+
+        Ok(index)
+    }
+
+    /// Returns true if `value` is stored in the tree.
+    pub fn contains(&self, value: impl Into<SparseKey>) -> bool {
+        self.indices.contains_key(&value.into())
+    }
+
+    /// Returns a proof that `value` is stored in the tree, to be checked
+    /// with [`verify_membership`].
+    ///
+    /// Fails with [`MerkleError::KeyNotFound`] if it is not.
+    pub fn generate_membership_proof(
+        &self,
+        value: impl Into<SparseKey>,
+    ) -> Result<IndexedMerkleProof<H::Digest>, MerkleError> {
+        match self.indices.get(&value.into()) {
+            Some(&index) => self.leaf_proof(index),
+            None => Err(MerkleError::KeyNotFound),
+        }
+    }
+
+    /// Returns a proof that `value` is not stored in the tree, to be
+    /// checked with [`verify_non_membership`].
+    ///
+    /// Fails with [`MerkleError::KeyExists`] if it is.
+    pub fn generate_non_membership_proof(
+        &self,
+        value: impl Into<SparseKey>,
+    ) -> Result<IndexedMerkleProof<H::Digest>, MerkleError> {
+        let value = value.into();
+        if self.indices.contains_key(&value) {
+            return Err(MerkleError::KeyExists);
+        }
+
+        self.leaf_proof(self.low_leaf(&value).0)
+    }
+
+    /// Returns the current root of the tree.
+    pub fn root(&self) -> H::Digest {
+        self.tree.root()
+    }
+
+    /// Returns the leaf at `index`, if it has been added.
+    pub fn leaf(&self, index: usize) -> Option<&IndexedLeaf> {
+        self.tree.leaf(index)
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.tree.depth()
+    }
+
+    /// Returns the number of values inserted, not counting zero.
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    /// Returns true if no values have been inserted.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Returns the position and contents of the leaf holding the largest
+    // value smaller than `value`.
+    fn low_leaf(&self, value: &SparseKey) -> (usize, IndexedLeaf) {
+        let (_, &index) = self.indices.range(..value).next_back().unwrap();
+        (index, *self.tree.leaf(index).unwrap())
+    }
+
+    fn leaf_proof(&self, index: usize) -> Result<IndexedMerkleProof<H::Digest>, MerkleError> {
+        Ok(IndexedMerkleProof {
+            leaf: *self.tree.leaf(index).unwrap(),
+            proof: self.tree.generate_proof(index)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leaf_encoding() -> Result<(), String> {
+        let leaf = IndexedLeaf {
+            value: SparseKey::from(1u64),
+            next_index: 2,
+            next_value: SparseKey::from(3u64),
+        };
+        let bytes = leaf.encode_leaf();
+
+        assert_eq!(bytes.len(), 72);
+        assert_eq!(bytes[31], 1);
+        assert_eq!(bytes[32..40], [2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(bytes[71], 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_links_sorted_values() -> Result<(), String> {
+        let mut mt = IndexedMerkleTree::<Sha256>::new(3);
+
+        mt.insert(30u64).unwrap();
+        mt.insert(10u64).unwrap();
+        mt.insert(20u64).unwrap();
+
+        let mut values = vec![];
+        let mut leaf = mt.leaf(0).unwrap();
+        while leaf.next_index != 0 {
+            leaf = mt.leaf(leaf.next_index).unwrap();
+            values.push(leaf.value);
+        }
+        assert_eq!(
+            values,
+            [
+                SparseKey::from(10u64),
+                SparseKey::from(20u64),
+                SparseKey::from(30u64)
+            ]
+        );
+
+        Ok(())
+    }
+}
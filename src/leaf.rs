@@ -0,0 +1,187 @@
+//! Values that can be stored in the leaves of a tree.
+
+use std::borrow::Cow;
+
+use crate::MerkleHasher;
+
+/// Values with a canonical byte encoding that is hashed into a leaf.
+///
+/// Integers are encoded as fixed-width little-endian bytes, which is also
+/// how SCALE encodes them, so a `u128` leaf hashes the same as a
+/// `Scale(u128)` leaf.
+pub trait EncodeLeaf {
+    /// Returns the bytes hashed into the leaf.
+    fn encode_leaf(&self) -> Cow<'_, [u8]>;
+}
+
+/// Values that can be hashed into a leaf of a tree built with `H`.
+///
+/// Implemented for every [`EncodeLeaf`] type and for [`PreHashed`]
+/// digests.
+pub trait Leaf<H: MerkleHasher> {
+    /// Returns the hash stored in the leaf.
+    fn leaf_hash(&self) -> H::Digest;
+}
+
+impl<H: MerkleHasher, T: EncodeLeaf + ?Sized> Leaf<H> for T {
+    fn leaf_hash(&self) -> H::Digest {
+        H::hash_leaf(&self.encode_leaf())
+    }
+}
+
+/// A leaf hash computed elsewhere, stored as is without hashing it again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PreHashed<D>(pub D);
+
+impl<H: MerkleHasher> Leaf<H> for PreHashed<H::Digest> {
+    fn leaf_hash(&self) -> H::Digest {
+        self.0
+    }
+}
+
+impl<T: EncodeLeaf + ?Sized> EncodeLeaf for &T {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        (**self).encode_leaf()
+    }
+}
+
+impl EncodeLeaf for str {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+impl EncodeLeaf for String {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.as_bytes())
+    }
+}
+
+impl EncodeLeaf for [u8] {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl EncodeLeaf for Vec<u8> {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<const N: usize> EncodeLeaf for [u8; N] {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+macro_rules! impl_encode_leaf_int {
+    ($($t:ty),*) => {
+        $(
+            impl EncodeLeaf for $t {
+                fn encode_leaf(&self) -> Cow<'_, [u8]> {
+                    Cow::Owned(self.to_le_bytes().to_vec())
+                }
+            }
+        )*
+    };
+}
+
+impl_encode_leaf_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// A leaf holding any SCALE-encodable value, such as an
+/// `(account, amount)` tuple or a struct deriving `Encode`.
+#[cfg(feature = "scale")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Scale<T>(pub T);
+
+#[cfg(feature = "scale")]
+impl<T: scale::Encode> EncodeLeaf for Scale<T> {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.0.encode())
+    }
+}
+
+/// A leaf holding any serde-serializable value, encoded with bincode.
+///
+/// Panics when hashed if the value cannot be serialized by bincode, e.g.
+/// a sequence that does not report its length.
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Bincode<T>(pub T);
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> EncodeLeaf for Bincode<T> {
+    fn encode_leaf(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(bincode::serialize(&self.0).expect("leaf is not serializable"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LegacyHasher, Sha256};
+
+    #[test]
+    fn test_strings_hash_like_bytes() -> Result<(), String> {
+        let hash = <[u8] as Leaf<Sha256>>::leaf_hash(b"foo");
+
+        assert_eq!(<str as Leaf<Sha256>>::leaf_hash("foo"), hash);
+        assert_eq!(Leaf::<Sha256>::leaf_hash(&String::from("foo")), hash);
+        assert_eq!(Leaf::<Sha256>::leaf_hash(&b"foo".to_vec()), hash);
+        assert_eq!(Leaf::<Sha256>::leaf_hash(b"foo"), hash);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_string_leaf() -> Result<(), String> {
+        assert_eq!(
+            <str as Leaf<LegacyHasher>>::leaf_hash("foo"),
+            4506850079084802999
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_integers_are_little_endian() -> Result<(), String> {
+        assert_eq!(1u32.encode_leaf(), [1, 0, 0, 0].as_ref());
+        assert_eq!((-1i16).encode_leaf(), [0xff, 0xff].as_ref());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pre_hashed() -> Result<(), String> {
+        let hash = <str as Leaf<Sha256>>::leaf_hash("foo");
+
+        assert_eq!(Leaf::<Sha256>::leaf_hash(&PreHashed(hash)), hash);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "scale")]
+    #[test]
+    fn test_scale() -> Result<(), String> {
+        assert_eq!(
+            Leaf::<Sha256>::leaf_hash(&Scale(10u128)),
+            Leaf::<Sha256>::leaf_hash(&10u128)
+        );
+        assert_eq!(Scale(([1u8; 32], 10u128)).encode_leaf().len(), 32 + 16);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_bincode() -> Result<(), String> {
+        assert_eq!(
+            Bincode((String::from("alice"), 10u64)).encode_leaf(),
+            [5, 0, 0, 0, 0, 0, 0, 0, b'a', b'l', b'i', b'c', b'e', 10, 0, 0, 0, 0, 0, 0, 0]
+                .as_ref()
+        );
+
+        Ok(())
+    }
+}
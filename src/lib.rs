@@ -0,0 +1,864 @@
+//! A fixed-depth, append-only Merkle tree.
+//!
+//! Leaves are appended sequentially with [`MerkleTree::add_data`] and the
+//! intermediate nodes on the path to the root are recomputed as soon as a
+//! subtree is complete. Membership of a leaf can be proven with
+//! [`MerkleTree::generate_proof`] and checked with [`MerkleTree::verify`],
+//! or with [`verify_proof`] by anyone who only knows the root.
+//!
+//! [`SparseMerkleTree`] computes the same roots while only storing the
+//! nodes of non-empty subtrees, which allows depths of up to 256.
+//! [`IncrementalMerkleTree`] only keeps the frontier needed to append
+//! leaves and compute the root. [`MerkleLog`] grows with every leaf and
+//! is shaped like an RFC 6962 Certificate Transparency log, including its
+//! audit paths and consistency proofs.
+//! [`IndexedMerkleTree`] keeps its values linked in sorted order so that
+//! it can also prove that a value is absent, and [`SparseMerkleMap`] is a
+//! key-value map with proofs of both presence and absence.
+//! [`MerkleSumTree`] commits to the total of the balances of its leaves.
+//! [`MerkleMountainRange`] is an append-only accumulator without a
+//! capacity that can prove inclusion against any earlier size.
+//!
+//! Leaves can be any type implementing [`Leaf`]. The hash function is
+//! chosen through the [`MerkleHasher`] type parameter, which also fixes the
+//! [`TreeFormat`] used to encode leaves and nodes.
+
+mod error;
+mod hasher;
+mod history;
+mod incremental;
+mod indexed;
+mod leaf;
+mod log;
+mod map;
+mod mmr;
+mod proof;
+mod sparse;
+mod sum;
+
+pub use error::{MerkleError, RootMismatch};
+pub use hasher::{
+    zero_hashes, Blake2b256, Hash256, HasherId, Keccak256, LegacyHasher, MerkleHasher, Sha256,
+    TreeFormat, LEAF_PREFIX, NODE_PREFIX,
+};
+pub use history::{RootHistory, DEFAULT_ROOT_HISTORY_SIZE};
+pub use incremental::IncrementalMerkleTree;
+pub use indexed::{
+    verify_membership, verify_non_membership, IndexedLeaf, IndexedMerkleProof, IndexedMerkleTree,
+};
+#[cfg(feature = "serde")]
+pub use leaf::Bincode;
+#[cfg(feature = "scale")]
+pub use leaf::Scale;
+pub use leaf::{EncodeLeaf, Leaf, PreHashed};
+pub use log::{verify_audit_proof, verify_consistency, AuditProof, ConsistencyProof, MerkleLog};
+pub use map::{verify_map_proof, SparseMerkleMap, SparseMerkleMapProof};
+pub use mmr::{verify_mmr_proof, MerkleMountainRange, MmrProof};
+use proof::{fold_level, proof_root};
+pub use proof::{
+    verify_multiproof, verify_proof, verify_range_proof, MerkleProof, MultiProof, RangeProof,
+};
+pub use sparse::{verify_sparse_proof, SparseKey, SparseMerkleProof, SparseMerkleTree};
+pub use sum::{verify_sum_proof, MerkleSumTree, SumNode, SumProof};
+
+/// What [`MerkleTree::add_data`] does once the tree is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GrowthPolicy {
+    /// Reject the leaf with [`MerkleError::TreeFull`].
+    #[default]
+    Fixed,
+    /// Increase the depth by one, making the old root the left child of a
+    /// new root whose right subtree is empty, and add the leaf there.
+    ///
+    /// Every root changes with the depth. Proofs generated before the tree
+    /// grew are rejected by [`MerkleTree::verify`] and
+    /// [`MerkleTree::verify_against_any_recent_root`], even against the
+    /// roots at the old depth still among the recent roots, until they are
+    /// brought up to date with [`MerkleTree::upgrade_proof`].
+    Grow,
+}
+
+/// A Merkle tree of a given depth holding up to `2^depth` leaves.
+///
+/// With [`GrowthPolicy::Grow`] the depth is increased whenever a leaf is
+/// added to a full tree, and proofs generated before can be brought up to
+/// date with [`MerkleTree::upgrade_proof`].
+///
+/// The root depends on the depth as well as on the leaves, so the roots of
+/// two sizes of the tree cannot be tied together by a consistency proof.
+/// Use [`MerkleLog`], whose root only depends on the leaves, to prove that
+/// one version of a log is a prefix of another.
+#[derive(Debug)]
+pub struct MerkleTree<T, H: MerkleHasher = Sha256> {
+    depth: usize,
+    growth: GrowthPolicy,
+    expected_root: Option<H::Digest>,
+    history: RootHistory<H::Digest>,
+    // None for leaves that have been cleared
+    data: Vec<Option<T>>,
+    tree: Vec<Vec<Node<H::Digest>>>,
+    index: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node<D> {
+    hash: D,
+}
+
+impl<T: Leaf<H>, H: MerkleHasher> MerkleTree<T, H> {
+    /// Creates a tree of the given depth whose unfilled leaves hash the
+    /// empty string.
+    pub fn new(depth: usize) -> MerkleTree<T, H> {
+        let empty = Node {
+            hash: H::hash_leaf(b""),
+        };
+        let mut mt = MerkleTree {
+            depth,
+            growth: GrowthPolicy::Fixed,
+            expected_root: None,
+            history: RootHistory::new(DEFAULT_ROOT_HISTORY_SIZE),
+            data: Vec::with_capacity(usize::pow(2, depth as u32)),
+            tree: Vec::with_capacity(depth + 1),
+            index: 0,
+        };
+
+        // allocate space for tree
+        mt.tree.resize_with(depth + 1, Default::default);
+        // initialize leaf hash with zero values
+        mt.tree[depth].resize(usize::pow(2, depth as u32), empty);
+
+        // build intermediate nodes up to root
+        for d in (0..depth).rev() {
+            mt.tree[d].resize(usize::pow(2, d as u32), empty);
+            for i in 0..(usize::pow(2, d as u32)) {
+                mt.tree[d][i] = Node {
+                    hash: H::hash_nodes(
+                        &mt.tree[d + 1][2 * i].hash,
+                        &mt.tree[d + 1][2 * i + 1].hash,
+                    ),
+                };
+            }
+        }
+        mt.history.push(mt.root());
+
+        mt
+    }
+
+    /// Creates a tree like [`MerkleTree::new`] that is pinned to the root
+    /// it is expected to have once all leaves have been added. Use
+    /// [`MerkleTree::check_root`] to compare the computed root against it.
+    pub fn with_expected_root(depth: usize, expected_root: H::Digest) -> MerkleTree<T, H> {
+        let mut mt = MerkleTree::new(depth);
+        mt.expected_root = Some(expected_root);
+        mt
+    }
+
+    /// Appends `data` as the next leaf.
+    ///
+    /// Fails with [`MerkleError::TreeFull`] if the tree holds `2^depth`
+    /// leaves, unless its growth policy is [`GrowthPolicy::Grow`].
+    pub fn add_data(&mut self, data: T) -> Result<(), MerkleError> {
+        if self.index == self.capacity() {
+            match self.growth {
+                GrowthPolicy::Fixed => return Err(MerkleError::TreeFull),
+                GrowthPolicy::Grow => self.grow(),
+            }
+        }
+        self.set_leaf_hash(self.index, data.leaf_hash());
+        self.data.push(Some(data));
+        self.index += 1;
+        self.history.push(self.root());
+
+        Ok(())
+    }
+
+    /// Replaces the data of the leaf at `index`, which must already have
+    /// been added, and returns the old and new root.
+    pub fn update_leaf(
+        &mut self,
+        index: usize,
+        data: T,
+    ) -> Result<(H::Digest, H::Digest), MerkleError> {
+        let hash = data.leaf_hash();
+        self.replace_leaf(index, Some(data), hash)
+    }
+
+    /// Resets the leaf at `index`, which must already have been added, to
+    /// the empty leaf and returns the old and new root. The slot stays
+    /// taken, so later leaves keep their positions.
+    pub fn clear_leaf(&mut self, index: usize) -> Result<(H::Digest, H::Digest), MerkleError> {
+        self.replace_leaf(index, None, H::hash_leaf(b""))
+    }
+
+    fn replace_leaf(
+        &mut self,
+        index: usize,
+        data: Option<T>,
+        hash: H::Digest,
+    ) -> Result<(H::Digest, H::Digest), MerkleError> {
+        if index >= self.index {
+            return Err(MerkleError::IndexOutOfRange {
+                index,
+                len: self.index,
+            });
+        }
+
+        let old_root = self.root();
+        self.data[index] = data;
+        self.set_leaf_hash(index, hash);
+        self.history.push(self.root());
+
+        Ok((old_root, self.root()))
+    }
+
+    // Doubles the capacity by putting the current tree under a new root
+    // next to an empty subtree of the same depth.
+    fn grow(&mut self) {
+        let zero = zero_hashes::<H>(self.depth);
+        for (d, level) in self.tree.iter_mut().enumerate() {
+            let empty = Node {
+                hash: zero[self.depth - d],
+            };
+            level.resize(2 * level.len(), empty);
+        }
+        let root = Node {
+            hash: H::hash_nodes(&self.root(), &zero[self.depth]),
+        };
+        self.tree.insert(0, vec![root]);
+        self.depth += 1;
+    }
+
+    // Stores the hash of leaf `index` and recomputes the path up to the
+    // root so that it is always current.
+    fn set_leaf_hash(&mut self, index: usize, hash: H::Digest) {
+        self.tree[self.depth][index].hash = hash;
+
+        let mut i = index;
+        for d in (0..self.depth).rev() {
+            i /= 2;
+            self.tree[d][i] = Node {
+                hash: H::hash_nodes(
+                    &self.tree[d + 1][2 * i].hash,
+                    &self.tree[d + 1][2 * i + 1].hash,
+                ),
+            };
+        }
+    }
+
+    /// Returns a proof of membership for the leaf at `index` against the
+    /// current root.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if no leaf has been
+    /// added at `index`.
+    // TODO: memoize
+    pub fn generate_proof(&self, index: usize) -> Result<MerkleProof<H::Digest>, MerkleError> {
+        if index >= self.index {
+            return Err(MerkleError::IndexOutOfRange {
+                index,
+                len: self.index,
+            });
+        }
+
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut directions = Vec::with_capacity(self.depth);
+
+        let mut i = index;
+        for d in (1..self.depth + 1).rev() {
+            if i % 2 == 1 {
+                siblings.push(self.tree[d][i - 1].hash);
+                directions.push(false);
+            } else {
+                siblings.push(self.tree[d][i + 1].hash);
+                directions.push(true);
+            }
+            i /= 2;
+        }
+
+        Ok(MerkleProof {
+            leaf_index: index,
+            depth: self.depth,
+            hasher: H::ID,
+            root: self.root(),
+            siblings,
+            directions,
+        })
+    }
+
+    /// Returns a single proof of membership for the leaves at `indices`
+    /// against the current root, holding every sibling that cannot be
+    /// computed from the leaves themselves exactly once.
+    ///
+    /// The indices may be given in any order and with repetitions; the
+    /// leaves must be passed to [`verify_multiproof`] in the order of
+    /// [`MultiProof::leaf_indices`].
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if no leaf has been
+    /// added at one of the indices, or [`MerkleError::MalformedProof`] if
+    /// `indices` is empty.
+    pub fn generate_multiproof(
+        &self,
+        indices: &[usize],
+    ) -> Result<MultiProof<H::Digest>, MerkleError> {
+        let mut leaf_indices = indices.to_vec();
+        leaf_indices.sort_unstable();
+        leaf_indices.dedup();
+        match leaf_indices.last() {
+            None => return Err(MerkleError::MalformedProof),
+            Some(&index) if index >= self.index => {
+                return Err(MerkleError::IndexOutOfRange {
+                    index,
+                    len: self.index,
+                })
+            }
+            _ => {}
+        }
+
+        let mut nodes: Vec<(usize, H::Digest)> = leaf_indices
+            .iter()
+            .map(|&i| (i, self.tree[self.depth][i].hash))
+            .collect();
+        let mut siblings = Vec::new();
+        for d in (1..self.depth + 1).rev() {
+            nodes = fold_level::<H, _>(&nodes, |i| {
+                siblings.push(self.tree[d][i].hash);
+                Some(self.tree[d][i].hash)
+            })
+            .unwrap();
+        }
+
+        Ok(MultiProof {
+            leaf_indices,
+            depth: self.depth,
+            hasher: H::ID,
+            root: self.root(),
+            siblings,
+        })
+    }
+
+    /// Returns a proof that the leaves at positions `start..end` are part
+    /// of the current root, holding only the siblings along the left and
+    /// right edges of the range.
+    ///
+    /// Fails with [`MerkleError::InvalidRange`] if the range is empty, or
+    /// [`MerkleError::IndexOutOfRange`] if it extends past the leaves
+    /// added so far.
+    pub fn generate_range_proof(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> Result<RangeProof<H::Digest>, MerkleError> {
+        if start >= end {
+            return Err(MerkleError::InvalidRange { start, end });
+        }
+        if end > self.index {
+            return Err(MerkleError::IndexOutOfRange {
+                index: end - 1,
+                len: self.index,
+            });
+        }
+
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+
+        // the nodes between the first and last one of a level pair up
+        // with each other, so only the outermost ones need siblings
+        let (mut first, mut last) = (start, end - 1);
+        for d in (1..self.depth + 1).rev() {
+            if first % 2 == 1 {
+                left.push(self.tree[d][first - 1].hash);
+            }
+            if last % 2 == 0 {
+                right.push(self.tree[d][last + 1].hash);
+            }
+            first /= 2;
+            last /= 2;
+        }
+
+        Ok(RangeProof {
+            start,
+            end,
+            depth: self.depth,
+            hasher: H::ID,
+            root: self.root(),
+            left,
+            right,
+        })
+    }
+
+    /// Brings a proof generated before the tree grew up to the current
+    /// depth and root. The old tree is the left-most subtree of the
+    /// current one, so the leaf keeps its index; the siblings are read
+    /// from the tree rather than taken from the proof, which also
+    /// refreshes any that changed since the proof was generated.
+    ///
+    /// Fails with [`MerkleError::DepthMismatch`] if the proof is for a
+    /// deeper tree, [`MerkleError::HasherMismatch`] if it was made with
+    /// a different hasher, or [`MerkleError::MalformedProof`] if its leaf
+    /// index does not fit its depth.
+    pub fn upgrade_proof(
+        &self,
+        proof: &MerkleProof<H::Digest>,
+    ) -> Result<MerkleProof<H::Digest>, MerkleError> {
+        if proof.hasher != H::ID {
+            return Err(MerkleError::HasherMismatch);
+        }
+        if proof.depth > self.depth {
+            return Err(MerkleError::DepthMismatch {
+                expected: self.depth,
+                actual: proof.depth,
+            });
+        }
+
+        if proof.depth < usize::BITS as usize && proof.leaf_index >> proof.depth != 0 {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        self.generate_proof(proof.leaf_index)
+    }
+
+    /// Checks `proof` for `data` against the current root of the tree.
+    /// `data` may be of any leaf type hashing like `T`, e.g. a `str` for
+    /// a tree of `String`s.
+    ///
+    /// Fails if the proof was generated for a tree of a different depth
+    /// or is otherwise malformed, see [`verify_proof`].
+    pub fn verify(
+        &self,
+        data: &(impl Leaf<H> + ?Sized),
+        proof: &MerkleProof<H::Digest>,
+    ) -> Result<bool, MerkleError> {
+        if proof.depth != self.depth {
+            return Err(MerkleError::DepthMismatch {
+                expected: self.depth,
+                actual: proof.depth,
+            });
+        }
+
+        verify_proof::<H>(&self.root(), data, proof)
+    }
+
+    /// Checks `proof` for `data` against any of the recent roots of the
+    /// tree, so that proofs generated before the last few insertions are
+    /// still accepted.
+    ///
+    /// Fails like [`MerkleTree::verify`].
+    pub fn verify_against_any_recent_root(
+        &self,
+        data: &(impl Leaf<H> + ?Sized),
+        proof: &MerkleProof<H::Digest>,
+    ) -> Result<bool, MerkleError> {
+        if proof.depth != self.depth {
+            return Err(MerkleError::DepthMismatch {
+                expected: self.depth,
+                actual: proof.depth,
+            });
+        }
+
+        Ok(self.is_known_root(&proof_root::<H>(data, proof)?))
+    }
+
+    /// Returns true if `root` is the current root or one of the recent
+    /// roots kept in the root history.
+    pub fn is_known_root(&self, root: &H::Digest) -> bool {
+        self.history.contains(root)
+    }
+
+    /// Returns the recent roots of the tree, including the current one.
+    pub fn root_history(&self) -> &RootHistory<H::Digest> {
+        &self.history
+    }
+
+    /// Sets the number of recent roots kept, which defaults to
+    /// [`DEFAULT_ROOT_HISTORY_SIZE`].
+    ///
+    /// Panics if `size` is zero.
+    pub fn set_root_history_size(&mut self, size: usize) {
+        self.history.resize(size);
+    }
+
+    /// Returns the current root of the tree.
+    pub fn root(&self) -> H::Digest {
+        self.tree[0][0].hash
+    }
+
+    /// Returns what happens when a leaf is added to a full tree.
+    pub fn growth_policy(&self) -> GrowthPolicy {
+        self.growth
+    }
+
+    /// Sets what happens when a leaf is added to a full tree, which
+    /// defaults to [`GrowthPolicy::Fixed`].
+    pub fn set_growth_policy(&mut self, growth: GrowthPolicy) {
+        self.growth = growth;
+    }
+
+    /// Returns the root the tree was pinned to, if any.
+    pub fn expected_root(&self) -> Option<H::Digest> {
+        self.expected_root
+    }
+
+    /// Compares the current root against the pinned root. Trees without
+    /// a pinned root always pass.
+    pub fn check_root(&self) -> Result<(), RootMismatch<H::Digest>> {
+        match self.expected_root {
+            Some(expected) if expected != self.root() => Err(RootMismatch {
+                expected,
+                actual: self.root(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the data stored at leaf `index`, if it has been added and
+    /// not cleared.
+    pub fn leaf(&self, index: usize) -> Option<&T> {
+        self.data.get(index).and_then(Option::as_ref)
+    }
+
+    /// Returns the encoding the tree's hashes are computed with.
+    pub fn format(&self) -> TreeFormat {
+        H::FORMAT
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves added so far.
+    pub fn len(&self) -> usize {
+        self.index
+    }
+
+    /// Returns true if no leaves have been added.
+    pub fn is_empty(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Returns the number of leaves the tree can hold at its current depth.
+    pub fn capacity(&self) -> usize {
+        usize::pow(2, self.depth as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_depth_0() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(0);
+
+        mt.add_data("foo").unwrap();
+
+        assert_eq!(mt.tree[0][0].hash, 4506850079084802999);
+        assert_eq!(mt.root(), 4506850079084802999);
+        assert_eq!(mt.add_data("bar"), Err(MerkleError::TreeFull));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_depth_1() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(1);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+
+        assert_eq!(mt.tree[0][0].hash, 17075777630381501106);
+        assert_eq!(mt.root(), 17075777630381501106);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_grow() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(0);
+        mt.set_growth_policy(GrowthPolicy::Grow);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+
+        assert_eq!(mt.depth(), 1);
+        assert_eq!(mt.tree[1][0].hash, 4506850079084802999);
+        assert_eq!(mt.root(), 17075777630381501106);
+
+        mt.add_data("baz").unwrap();
+
+        assert_eq!(mt.depth(), 2);
+        assert_eq!(mt.tree[2].len(), 4);
+        assert_eq!(mt.tree[1][0].hash, 17075777630381501106);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_update_leaf() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(1);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("baz").unwrap();
+
+        let old_root = mt.root();
+        assert_eq!(
+            mt.update_leaf(1, "bar"),
+            Ok((old_root, 17075777630381501106))
+        );
+        assert_eq!(mt.tree[1][1].hash, 3676438629107045207);
+        assert_eq!(mt.leaf(1), Some(&"bar"));
+
+        assert_eq!(
+            mt.clear_leaf(0),
+            Ok((
+                17075777630381501106,
+                LegacyHasher::hash_nodes(&LegacyHasher::hash_leaf(b""), &3676438629107045207)
+            ))
+        );
+        assert_eq!(mt.leaf(0), None);
+        assert_eq!(mt.len(), 2);
+
+        assert_eq!(
+            mt.update_leaf(2, "baz"),
+            Err(MerkleError::IndexOutOfRange { index: 2, len: 2 })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_proof_depth_1() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(1);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+
+        let proof = mt.generate_proof(0).unwrap();
+        assert_eq!(proof.root, 17075777630381501106);
+        assert_eq!(proof.siblings, [3676438629107045207]);
+        assert_eq!(proof.directions, [true]);
+
+        let proof = mt.generate_proof(1).unwrap();
+        assert_eq!(proof.root, 17075777630381501106);
+        assert_eq!(proof.siblings, [4506850079084802999]);
+        assert_eq!(proof.directions, [false]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_proof_depth_2() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(2);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+        mt.add_data("baz").unwrap();
+        mt.add_data("yup").unwrap();
+
+        let proof = mt.generate_proof(0).unwrap();
+        assert_eq!(proof.root, 4778819754073447529);
+        assert_eq!(proof.siblings, [3676438629107045207, 9268692565628018440]);
+        assert_eq!(proof.directions, [true, true]);
+
+        let proof = mt.generate_proof(1).unwrap();
+        assert_eq!(proof.root, 4778819754073447529);
+        assert_eq!(proof.siblings, [4506850079084802999, 9268692565628018440]);
+        assert_eq!(proof.directions, [false, true]);
+
+        let proof = mt.generate_proof(2).unwrap();
+        assert_eq!(proof.root, 4778819754073447529);
+        assert_eq!(proof.siblings, [1968634300370677998, 17075777630381501106]);
+        assert_eq!(proof.directions, [true, false]);
+
+        let proof = mt.generate_proof(3).unwrap();
+        assert_eq!(proof.root, 4778819754073447529);
+        assert_eq!(proof.siblings, [16260972211344176173, 17075777630381501106]);
+        assert_eq!(proof.directions, [false, false]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_proof_depth_3() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(3);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+        mt.add_data("baz").unwrap();
+        mt.add_data("yup").unwrap();
+        mt.add_data("maw").unwrap();
+        mt.add_data("wap").unwrap();
+        mt.add_data("pit").unwrap();
+        mt.add_data("fos").unwrap();
+
+        println!("{:#?}", mt);
+
+        let proof = mt.generate_proof(0).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                3676438629107045207,
+                9268692565628018440,
+                1292560851973962312
+            ]
+        );
+        assert_eq!(proof.directions, [true, true, true]);
+
+        let proof = mt.generate_proof(1).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                4506850079084802999,
+                9268692565628018440,
+                1292560851973962312
+            ]
+        );
+        assert_eq!(proof.directions, [false, true, true]);
+
+        let proof = mt.generate_proof(2).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                1968634300370677998,
+                17075777630381501106,
+                1292560851973962312
+            ]
+        );
+        assert_eq!(proof.directions, [true, false, true]);
+
+        let proof = mt.generate_proof(3).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                16260972211344176173,
+                17075777630381501106,
+                1292560851973962312
+            ]
+        );
+        assert_eq!(proof.directions, [false, false, true]);
+
+        let proof = mt.generate_proof(4).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                14416090190412621920,
+                6756623144268557643,
+                4778819754073447529
+            ]
+        );
+        assert_eq!(proof.directions, [true, true, false]);
+
+        let proof = mt.generate_proof(5).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                5587210449854392903,
+                6756623144268557643,
+                4778819754073447529
+            ]
+        );
+        assert_eq!(proof.directions, [false, true, false]);
+
+        let proof = mt.generate_proof(6).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                9147698590242891024,
+                10865386958110225586,
+                4778819754073447529
+            ]
+        );
+        assert_eq!(proof.directions, [true, false, false]);
+
+        let proof = mt.generate_proof(7).unwrap();
+        assert_eq!(proof.root, 1556255166675498662);
+        assert_eq!(
+            proof.siblings,
+            [
+                10714775279812270610,
+                10865386958110225586,
+                4778819754073447529
+            ]
+        );
+        assert_eq!(proof.directions, [false, false, false]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_verify_depth_1() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(1);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+
+        assert!(mt.verify("foo", &mt.generate_proof(0).unwrap()).unwrap());
+        assert!(mt.verify("bar", &mt.generate_proof(1).unwrap()).unwrap());
+        assert!(!mt.verify("bar", &mt.generate_proof(0).unwrap()).unwrap());
+        assert!(!mt.verify("foo", &mt.generate_proof(1).unwrap()).unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_verify_depth_2() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(2);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+        mt.add_data("baz").unwrap();
+        mt.add_data("yup").unwrap();
+
+        assert!(mt.verify("foo", &mt.generate_proof(0).unwrap()).unwrap());
+        assert!(mt.verify("bar", &mt.generate_proof(1).unwrap()).unwrap());
+        assert!(mt.verify("baz", &mt.generate_proof(2).unwrap()).unwrap());
+        assert!(mt.verify("yup", &mt.generate_proof(3).unwrap()).unwrap());
+        assert!(!mt.verify("bar", &mt.generate_proof(0).unwrap()).unwrap());
+        assert!(!mt.verify("baz", &mt.generate_proof(1).unwrap()).unwrap());
+        assert!(!mt.verify("yup", &mt.generate_proof(2).unwrap()).unwrap());
+        assert!(!mt.verify("foo", &mt.generate_proof(3).unwrap()).unwrap());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merkle_verify_depth_3() -> Result<(), String> {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(3);
+
+        mt.add_data("foo").unwrap();
+        mt.add_data("bar").unwrap();
+        mt.add_data("baz").unwrap();
+        mt.add_data("yup").unwrap();
+        mt.add_data("maw").unwrap();
+        mt.add_data("wap").unwrap();
+        mt.add_data("pit").unwrap();
+        mt.add_data("fos").unwrap();
+
+        println!("{:#?}", mt);
+
+        assert!(mt.verify("foo", &mt.generate_proof(0).unwrap()).unwrap());
+        assert!(mt.verify("bar", &mt.generate_proof(1).unwrap()).unwrap());
+        assert!(mt.verify("baz", &mt.generate_proof(2).unwrap()).unwrap());
+        assert!(mt.verify("yup", &mt.generate_proof(3).unwrap()).unwrap());
+        assert!(mt.verify("maw", &mt.generate_proof(4).unwrap()).unwrap());
+        assert!(mt.verify("wap", &mt.generate_proof(5).unwrap()).unwrap());
+        assert!(mt.verify("pit", &mt.generate_proof(6).unwrap()).unwrap());
+        assert!(mt.verify("fos", &mt.generate_proof(7).unwrap()).unwrap());
+        assert!(!mt.verify("bar", &mt.generate_proof(0).unwrap()).unwrap());
+        assert!(!mt.verify("baz", &mt.generate_proof(1).unwrap()).unwrap());
+        assert!(!mt.verify("yup", &mt.generate_proof(2).unwrap()).unwrap());
+        assert!(!mt.verify("maw", &mt.generate_proof(3).unwrap()).unwrap());
+        assert!(!mt.verify("wap", &mt.generate_proof(4).unwrap()).unwrap());
+        assert!(!mt.verify("pit", &mt.generate_proof(5).unwrap()).unwrap());
+        assert!(!mt.verify("fos", &mt.generate_proof(6).unwrap()).unwrap());
+        assert!(!mt.verify("foo", &mt.generate_proof(7).unwrap()).unwrap());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,401 @@
+//! An append-only Merkle tree shaped like a Certificate Transparency log.
+
+use crate::{HasherId, Leaf, MerkleError, MerkleHasher, Sha256};
+
+/// A proof that a leaf is part of an RFC 6962 tree of a given size, i.e.
+/// the audit path `PATH(m, D[n])` of RFC 6962 section 2.1.1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditProof<D> {
+    /// The position of the leaf in the tree.
+    pub leaf_index: usize,
+    /// The number of leaves in the tree the proof was generated from.
+    pub tree_size: usize,
+    /// The hash function the tree was built with.
+    pub hasher: HasherId,
+    /// The root of the tree when the proof was generated. Verification
+    /// checks against a root supplied by the verifier, not this one.
+    pub root: D,
+    /// The audit path, starting with the sibling of the leaf. Its length
+    /// depends on the position of the leaf and the size of the tree.
+    pub siblings: Vec<D>,
+}
+
+/// Checks that `data` is the leaf at `proof.leaf_index` of an RFC 6962
+/// tree of `proof.tree_size` leaves whose root is `root`.
+///
+/// Fails if the proof was made with a different hasher or if its audit
+/// path does not have the length the index and tree size call for.
+pub fn verify_audit_proof<H: MerkleHasher>(
+    root: &H::Digest,
+    data: &(impl Leaf<H> + ?Sized),
+    proof: &AuditProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if proof.leaf_index >= proof.tree_size {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    // RFC 9162 section 2.1.3.2
+    let mut hash = data.leaf_hash();
+    let mut i = proof.leaf_index;
+    let mut last = proof.tree_size - 1;
+    for sibling in &proof.siblings {
+        if last == 0 {
+            return Err(MerkleError::MalformedProof);
+        }
+        if i % 2 == 1 || i == last {
+            hash = H::hash_nodes(sibling, &hash);
+            // skip the levels where the node has no right sibling
+            while i % 2 == 0 && i != 0 {
+                i /= 2;
+                last /= 2;
+            }
+        } else {
+            hash = H::hash_nodes(&hash, sibling);
+        }
+        i /= 2;
+        last /= 2;
+    }
+    if last != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    Ok(hash == *root)
+}
+
+/// A proof that an RFC 6962 tree of some size is a prefix of the tree
+/// after more leaves were appended, i.e. the consistency proof
+/// `PROOF(m, D[n])` of RFC 6962 section 2.1.2.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsistencyProof<D> {
+    /// The hash function the tree was built with.
+    pub hasher: HasherId,
+    /// The subtree roots of the proof in the order RFC 6962 lists them,
+    /// starting with the one closest to the leaves.
+    pub path: Vec<D>,
+}
+
+/// Checks that the RFC 6962 tree of `old_size` leaves with root
+/// `old_root` is a prefix of the tree of `new_size` leaves with root
+/// `new_root`.
+///
+/// Fails with [`MerkleError::InvalidRange`] if `old_size` is larger than
+/// `new_size`, or if the proof was made with a different hasher or its
+/// path does not have the length the sizes call for.
+pub fn verify_consistency<H: MerkleHasher>(
+    old_root: &H::Digest,
+    new_root: &H::Digest,
+    old_size: usize,
+    new_size: usize,
+    proof: &ConsistencyProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if old_size > new_size {
+        return Err(MerkleError::InvalidRange {
+            start: old_size,
+            end: new_size,
+        });
+    }
+    if old_size == 0 || old_size == new_size {
+        if !proof.path.is_empty() {
+            return Err(MerkleError::MalformedProof);
+        }
+        // the empty tree is a prefix of every tree
+        if old_size == 0 {
+            return Ok(*old_root == H::hash_empty());
+        }
+        return Ok(old_root == new_root);
+    }
+
+    // RFC 9162 section 2.1.4.2; the proof of a complete old tree leaves
+    // out its root, which the verifier already has
+    let mut path = proof.path.iter();
+    let first = if old_size.is_power_of_two() {
+        old_root
+    } else {
+        path.next().ok_or(MerkleError::MalformedProof)?
+    };
+    // old_node and new_node are fn and sn of the RFC
+    let mut old_node = old_size - 1;
+    let mut new_node = new_size - 1;
+    while old_node % 2 == 1 {
+        old_node /= 2;
+        new_node /= 2;
+    }
+    let mut old = *first;
+    let mut new = *first;
+    for c in path {
+        if new_node == 0 {
+            return Err(MerkleError::MalformedProof);
+        }
+        if old_node % 2 == 1 || old_node == new_node {
+            old = H::hash_nodes(c, &old);
+            new = H::hash_nodes(c, &new);
+            // skip the levels where the old node is a left child
+            if old_node != 0 {
+                let skip = old_node.trailing_zeros();
+                old_node >>= skip;
+                new_node >>= skip;
+            }
+        } else {
+            new = H::hash_nodes(&new, c);
+        }
+        old_node /= 2;
+        new_node /= 2;
+    }
+    if new_node != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    Ok(old == *old_root && new == *new_root)
+}
+
+/// An append-only Merkle tree whose size is the number of leaves, shaped
+/// as described in RFC 6962.
+///
+/// Unlike [`MerkleTree`](crate::MerkleTree) there is no fixed capacity
+/// and no padding: a tree of `n` leaves splits into a left subtree holding
+/// the largest power of two smaller than `n` leaves and a right subtree
+/// holding the rest. With [`Sha256`] the roots and audit paths are those
+/// of a Certificate Transparency log over the same entries.
+#[derive(Debug)]
+pub struct MerkleLog<T, H: MerkleHasher = Sha256> {
+    data: Vec<T>,
+    // levels[h][i] is the root of the complete subtree holding leaves
+    // i * 2^h to (i + 1) * 2^h - 1
+    levels: Vec<Vec<H::Digest>>,
+}
+
+impl<T: Leaf<H>, H: MerkleHasher> MerkleLog<T, H> {
+    /// Creates an empty tree.
+    pub fn new() -> MerkleLog<T, H> {
+        MerkleLog {
+            data: Vec::new(),
+            levels: vec![Vec::new()],
+        }
+    }
+
+    /// Appends `data` as the next leaf.
+    pub fn add_data(&mut self, data: T) {
+        let mut hash = data.leaf_hash();
+        self.data.push(data);
+
+        let mut h = 0;
+        loop {
+            self.levels[h].push(hash);
+            let len = self.levels[h].len();
+            if len % 2 == 1 {
+                break;
+            }
+            hash = H::hash_nodes(&self.levels[h][len - 2], &self.levels[h][len - 1]);
+            h += 1;
+            if h == self.levels.len() {
+                self.levels.push(Vec::new());
+            }
+        }
+    }
+
+    /// Returns the current root of the tree, which for an empty tree is
+    /// the hash of empty input.
+    pub fn root(&self) -> H::Digest {
+        if self.data.is_empty() {
+            return H::hash_empty();
+        }
+        self.subtree_root(0, self.data.len())
+    }
+
+    /// Returns the audit path for the leaf at `index` against the current
+    /// root.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if no leaf has been
+    /// added at `index`.
+    pub fn generate_proof(&self, index: usize) -> Result<AuditProof<H::Digest>, MerkleError> {
+        if index >= self.data.len() {
+            return Err(MerkleError::IndexOutOfRange {
+                index,
+                len: self.data.len(),
+            });
+        }
+
+        // walk down from the root, collecting the sibling subtrees, then
+        // reverse to list them from the leaf up
+        let mut siblings = Vec::new();
+        let (mut start, mut end) = (0, self.data.len());
+        while end - start > 1 {
+            let mid = start + split(end - start);
+            if index < mid {
+                siblings.push(self.subtree_root(mid, end));
+                end = mid;
+            } else {
+                siblings.push(self.subtree_root(start, mid));
+                start = mid;
+            }
+        }
+        siblings.reverse();
+
+        Ok(AuditProof {
+            leaf_index: index,
+            tree_size: self.data.len(),
+            hasher: H::ID,
+            root: self.root(),
+            siblings,
+        })
+    }
+
+    /// Checks `proof` for `data` against the current root of the tree.
+    ///
+    /// Fails if the proof was generated for a tree of a different size or
+    /// is otherwise malformed, see [`verify_audit_proof`].
+    pub fn verify(
+        &self,
+        data: &(impl Leaf<H> + ?Sized),
+        proof: &AuditProof<H::Digest>,
+    ) -> Result<bool, MerkleError> {
+        if proof.tree_size != self.data.len() {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        verify_audit_proof::<H>(&self.root(), data, proof)
+    }
+
+    /// Returns a proof that the tree of `old_size` leaves is a prefix of
+    /// the tree of `new_size` leaves.
+    ///
+    /// Fails with [`MerkleError::InvalidRange`] if `old_size` is larger
+    /// than `new_size`, or [`MerkleError::IndexOutOfRange`] if fewer than
+    /// `new_size` leaves have been added.
+    pub fn consistency_proof(
+        &self,
+        old_size: usize,
+        new_size: usize,
+    ) -> Result<ConsistencyProof<H::Digest>, MerkleError> {
+        if old_size > new_size {
+            return Err(MerkleError::InvalidRange {
+                start: old_size,
+                end: new_size,
+            });
+        }
+        if new_size > self.data.len() {
+            return Err(MerkleError::IndexOutOfRange {
+                index: new_size,
+                len: self.data.len(),
+            });
+        }
+
+        let mut path = Vec::new();
+        if old_size > 0 && old_size < new_size {
+            self.subproof(old_size, 0, new_size, true, &mut path);
+        }
+
+        Ok(ConsistencyProof {
+            hasher: H::ID,
+            path,
+        })
+    }
+
+    /// Returns the data stored at leaf `index`, if it has been added.
+    pub fn leaf(&self, index: usize) -> Option<&T> {
+        self.data.get(index)
+    }
+
+    /// Returns the number of leaves added so far.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if no leaves have been added.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    // Returns the root of the RFC 6962 tree over leaves start..end, where
+    // start is a multiple of the largest power of two below end - start,
+    // as it is for every subtree of the whole tree.
+    fn subtree_root(&self, start: usize, end: usize) -> H::Digest {
+        let size = end - start;
+        if size.is_power_of_two() {
+            let h = size.trailing_zeros() as usize;
+            return self.levels[h][start >> h];
+        }
+
+        let mid = start + split(size);
+        H::hash_nodes(&self.subtree_root(start, mid), &self.subtree_root(mid, end))
+    }
+
+    // Appends SUBPROOF(m, D[start:end], complete) of RFC 6962 section
+    // 2.1.2 to path, where complete is true while the subtree holding the
+    // first m leaves is the old tree itself, whose root is not needed.
+    fn subproof(
+        &self,
+        m: usize,
+        start: usize,
+        end: usize,
+        complete: bool,
+        path: &mut Vec<H::Digest>,
+    ) {
+        if m == end - start {
+            if !complete {
+                path.push(self.subtree_root(start, end));
+            }
+            return;
+        }
+
+        let mid = start + split(end - start);
+        if m <= mid - start {
+            self.subproof(m, start, mid, complete, path);
+            path.push(self.subtree_root(mid, end));
+        } else {
+            self.subproof(m - (mid - start), mid, end, false, path);
+            path.push(self.subtree_root(start, mid));
+        }
+    }
+}
+
+impl<T: Leaf<H>, H: MerkleHasher> Default for MerkleLog<T, H> {
+    fn default() -> MerkleLog<T, H> {
+        MerkleLog::new()
+    }
+}
+
+// Returns the largest power of two smaller than n, for n > 1.
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() -> Result<(), String> {
+        assert_eq!(split(2), 1);
+        assert_eq!(split(3), 2);
+        assert_eq!(split(4), 2);
+        assert_eq!(split(5), 4);
+        assert_eq!(split(8), 4);
+        assert_eq!(split(9), 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_levels() -> Result<(), String> {
+        let mut log = MerkleLog::<&str, Sha256>::new();
+        for data in &["foo", "bar", "baz"] {
+            log.add_data(*data);
+        }
+
+        assert_eq!(log.levels.len(), 2);
+        assert_eq!(log.levels[0].len(), 3);
+        assert_eq!(
+            log.levels[1],
+            [Sha256::hash_nodes(&log.levels[0][0], &log.levels[0][1])]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//! A verifiable key-value map on top of a sparse Merkle tree.
+
+use std::collections::HashMap;
+
+use crate::{
+    zero_hashes, EncodeLeaf, Hash256, HasherId, MerkleError, MerkleHasher, PreHashed, Sha256,
+    SparseKey, SparseMerkleTree,
+};
+
+/// A proof that a key of a [`SparseMerkleMap`] maps to a value, or to
+/// nothing.
+///
+/// Most siblings on the 256-step path of a key are empty subtrees, so
+/// only the others are included and a bitmap records which ones they
+/// are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMerkleMapProof {
+    /// The hash function the map was built with.
+    pub hasher: HasherId,
+    /// The root of the map when the proof was generated. Verification
+    /// checks against a root supplied by the verifier, not this one.
+    pub root: Hash256,
+    /// Bit `h % 8` of byte `h / 8` is set if the sibling at height `h`,
+    /// counting from the leaf, is not empty.
+    pub bitmap: [u8; 32],
+    /// The non-empty siblings, starting with the one closest to the leaf.
+    pub siblings: Vec<Hash256>,
+}
+
+/// Checks that `key` maps to `value` in a map whose root is `root`, where
+/// a `value` of `None` means that the key is absent.
+///
+/// Fails if the proof was made with a different hasher or if its bitmap
+/// does not match the number of siblings.
+pub fn verify_map_proof<H: MerkleHasher<Digest = Hash256>>(
+    root: &Hash256,
+    key: &(impl EncodeLeaf + ?Sized),
+    value: Option<&(impl EncodeLeaf + ?Sized)>,
+    proof: &SparseMerkleMapProof,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    let count: u32 = proof.bitmap.iter().map(|b| b.count_ones()).sum();
+    if count as usize != proof.siblings.len() {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let zero = zero_hashes::<H>(256);
+    let path = key_path::<H>(key);
+    let mut hash = match value {
+        Some(value) => entry_hash::<H>(&path, value),
+        None => zero[0],
+    };
+    let mut siblings = proof.siblings.iter();
+    for (h, empty) in zero.iter().enumerate().take(256) {
+        let sibling = if proof.bitmap[h / 8] >> (h % 8) & 1 == 1 {
+            siblings.next().unwrap()
+        } else {
+            empty
+        };
+        // bit h of the path, counting from the least significant, is the
+        // direction of the step at height h
+        hash = if path.as_bytes()[31 - h / 8] >> (h % 8) & 1 == 1 {
+            H::hash_nodes(sibling, &hash)
+        } else {
+            H::hash_nodes(&hash, sibling)
+        };
+    }
+
+    Ok(hash == *root)
+}
+
+/// A map whose contents are committed to by a single root, supporting
+/// proofs that a key maps to a value and that a key is absent.
+///
+/// Every key is hashed into a 256-bit path of a [`SparseMerkleTree`] of
+/// depth 256, and its leaf commits to both the path and the value, so a
+/// present key is never confused with an absent one. Only the nodes of
+/// non-empty subtrees are stored.
+#[derive(Debug)]
+pub struct SparseMerkleMap<K, V, H: MerkleHasher<Digest = Hash256> = Sha256> {
+    tree: SparseMerkleTree<PreHashed<Hash256>, H>,
+    entries: HashMap<SparseKey, (K, V)>,
+    // zero[h] is the hash of an empty subtree of height h
+    zero: Vec<Hash256>,
+}
+
+impl<K: EncodeLeaf, V: EncodeLeaf, H: MerkleHasher<Digest = Hash256>> SparseMerkleMap<K, V, H> {
+    /// Creates an empty map.
+    pub fn new() -> SparseMerkleMap<K, V, H> {
+        SparseMerkleMap {
+            tree: SparseMerkleTree::new(256),
+            entries: HashMap::new(),
+            zero: zero_hashes::<H>(256),
+        }
+    }
+
+    /// Maps `key` to `value`, returning the value it previously mapped to.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let path = key_path::<H>(&key);
+        let hash = entry_hash::<H>(&path, &value);
+        self.tree.insert(path, PreHashed(hash)).unwrap();
+
+        self.entries.insert(path, (key, value)).map(|(_, v)| v)
+    }
+
+    /// Removes `key`, returning the value it mapped to.
+    pub fn remove(&mut self, key: &(impl EncodeLeaf + ?Sized)) -> Option<V> {
+        let path = key_path::<H>(key);
+        self.tree.remove(path).unwrap();
+
+        self.entries.remove(&path).map(|(_, v)| v)
+    }
+
+    /// Returns the value `key` maps to, if any.
+    pub fn get(&self, key: &(impl EncodeLeaf + ?Sized)) -> Option<&V> {
+        self.entries.get(&key_path::<H>(key)).map(|(_, v)| v)
+    }
+
+    /// Returns true if `key` maps to a value.
+    pub fn contains_key(&self, key: &(impl EncodeLeaf + ?Sized)) -> bool {
+        self.entries.contains_key(&key_path::<H>(key))
+    }
+
+    /// Returns the value `key` maps to, if any, together with a proof of
+    /// it, or of its absence, against the current root.
+    pub fn get_with_proof(
+        &self,
+        key: &(impl EncodeLeaf + ?Sized),
+    ) -> (Option<&V>, SparseMerkleMapProof) {
+        let path = key_path::<H>(key);
+        let full = self.tree.generate_proof(path).unwrap();
+
+        let mut bitmap = [0; 32];
+        let mut siblings = Vec::new();
+        for (h, sibling) in full.siblings.into_iter().enumerate() {
+            if sibling != self.zero[h] {
+                bitmap[h / 8] |= 1 << (h % 8);
+                siblings.push(sibling);
+            }
+        }
+
+        let proof = SparseMerkleMapProof {
+            hasher: H::ID,
+            root: self.root(),
+            bitmap,
+            siblings,
+        };
+        (self.entries.get(&path).map(|(_, v)| v), proof)
+    }
+
+    /// Returns the current root of the map.
+    pub fn root(&self) -> Hash256 {
+        self.tree.root()
+    }
+
+    /// Returns an iterator over the keys and values in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.values().map(|(k, v)| (k, v))
+    }
+
+    /// Returns the number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<K: EncodeLeaf, V: EncodeLeaf, H: MerkleHasher<Digest = Hash256>> Default
+    for SparseMerkleMap<K, V, H>
+{
+    fn default() -> SparseMerkleMap<K, V, H> {
+        SparseMerkleMap::new()
+    }
+}
+
+// Returns the position of the leaf of `key`.
+fn key_path<H: MerkleHasher<Digest = Hash256>>(key: &(impl EncodeLeaf + ?Sized)) -> SparseKey {
+    SparseKey::from_bytes(H::hash_leaf(&key.encode_leaf()))
+}
+
+// Returns the leaf hash of an entry, which covers the path so that no
+// entry hashes like an empty leaf.
+fn entry_hash<H: MerkleHasher<Digest = Hash256>>(
+    path: &SparseKey,
+    value: &(impl EncodeLeaf + ?Sized),
+) -> Hash256 {
+    let mut data = path.as_bytes().to_vec();
+    data.extend_from_slice(&value.encode_leaf());
+    H::hash_leaf(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_is_not_empty() -> Result<(), String> {
+        let path = key_path::<Sha256>("foo");
+
+        assert_ne!(entry_hash::<Sha256>(&path, ""), Sha256::hash_leaf(b""));
+        assert_ne!(
+            entry_hash::<Sha256>(&path, "bar"),
+            entry_hash::<Sha256>(&key_path::<Sha256>("baz"), "bar")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+//! A Merkle Mountain Range, an append-only accumulator of unbounded size.
+
+use crate::proof::fold_path;
+use crate::{HasherId, Leaf, MerkleError, MerkleHasher, Sha256};
+
+/// A proof that a leaf is part of a Merkle Mountain Range of a given
+/// size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MmrProof<D> {
+    /// The position of the leaf among the leaves.
+    pub leaf_index: usize,
+    /// The number of leaves of the range the proof was generated for.
+    pub mmr_size: usize,
+    /// The hash function the range was built with.
+    pub hasher: HasherId,
+    /// The root of the range at `mmr_size` leaves. Verification checks
+    /// against a root supplied by the verifier, not this one.
+    pub root: D,
+    /// The sibling hashes on the path from the leaf up to its peak,
+    /// starting with the leaf's own sibling.
+    pub siblings: Vec<D>,
+    /// The other peaks from left to right.
+    pub peaks: Vec<D>,
+}
+
+/// Checks that `data` is the leaf at `proof.leaf_index` of a Merkle
+/// Mountain Range of `proof.mmr_size` leaves whose root is `root`.
+///
+/// Fails if the proof was made with a different hasher or if its
+/// siblings and peaks do not have the shape the index and size call for.
+pub fn verify_mmr_proof<H: MerkleHasher>(
+    root: &H::Digest,
+    data: &(impl Leaf<H> + ?Sized),
+    proof: &MmrProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if proof.leaf_index >= proof.mmr_size
+        || proof.peaks.len() + 1 != proof.mmr_size.count_ones() as usize
+    {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let (k, height, offset) = peak_of(proof.leaf_index, proof.mmr_size);
+    if proof.siblings.len() != height {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    // a left child has its sibling on the right
+    let local = proof.leaf_index - offset;
+    let directions: Vec<bool> = (0..height).map(|h| (local >> h) % 2 == 0).collect();
+    let peak = fold_path::<H>(data.leaf_hash(), &proof.siblings, &directions);
+
+    let mut peaks = proof.peaks.clone();
+    peaks.insert(k, peak);
+
+    Ok(bag_peaks::<H>(&peaks) == *root)
+}
+
+/// An append-only accumulator made of a list of perfect Merkle trees, the
+/// peaks, of strictly decreasing heights.
+///
+/// Appending a leaf adds a peak of height 0 and merges peaks of equal
+/// height, which takes amortized O(1) hashes, so there is no capacity to
+/// choose up front. Nodes are never changed once written, which lets the
+/// root of, and proofs against, every earlier size be produced later. The
+/// root bags the peaks from right to left with the same [`MerkleHasher`]
+/// the trees use.
+#[derive(Debug)]
+pub struct MerkleMountainRange<T, H: MerkleHasher = Sha256> {
+    data: Vec<T>,
+    // every node in the order it was written, each parent right after its
+    // right child
+    nodes: Vec<H::Digest>,
+}
+
+impl<T: Leaf<H>, H: MerkleHasher> MerkleMountainRange<T, H> {
+    /// Creates an empty range.
+    pub fn new() -> MerkleMountainRange<T, H> {
+        MerkleMountainRange {
+            data: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Appends `data` as the next leaf.
+    pub fn add_data(&mut self, data: T) {
+        self.nodes.push(data.leaf_hash());
+
+        // every trailing one bit of the old size is a peak of that height
+        // to merge with
+        let mut size = self.data.len();
+        let mut h = 0;
+        while size % 2 == 1 {
+            let right = self.nodes.len() - 1;
+            let left = right - subtree_nodes(h);
+            let parent = H::hash_nodes(&self.nodes[left], &self.nodes[right]);
+            self.nodes.push(parent);
+            size /= 2;
+            h += 1;
+        }
+        self.data.push(data);
+    }
+
+    /// Returns the current root, which for an empty range is the hash of
+    /// empty input.
+    pub fn root(&self) -> H::Digest {
+        bag_peaks::<H>(&self.peaks_at(self.data.len()))
+    }
+
+    /// Returns the root the range had when it held `size` leaves.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if fewer than `size`
+    /// leaves have been added.
+    pub fn root_at(&self, size: usize) -> Result<H::Digest, MerkleError> {
+        if size > self.data.len() {
+            return Err(MerkleError::IndexOutOfRange {
+                index: size,
+                len: self.data.len(),
+            });
+        }
+
+        Ok(bag_peaks::<H>(&self.peaks_at(size)))
+    }
+
+    /// Returns the roots of the peaks from left to right.
+    pub fn peaks(&self) -> Vec<H::Digest> {
+        self.peaks_at(self.data.len())
+    }
+
+    /// Returns a proof of inclusion for the leaf at `index` against the
+    /// current root.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if no leaf has been
+    /// added at `index`.
+    pub fn generate_proof(&self, index: usize) -> Result<MmrProof<H::Digest>, MerkleError> {
+        self.generate_proof_at(index, self.data.len())
+    }
+
+    /// Returns a proof of inclusion for the leaf at `index` against the
+    /// root the range had when it held `size` leaves.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if `index` is not
+    /// smaller than `size` or `size` leaves have not been added yet.
+    pub fn generate_proof_at(
+        &self,
+        index: usize,
+        size: usize,
+    ) -> Result<MmrProof<H::Digest>, MerkleError> {
+        let root = self.root_at(size)?;
+        if index >= size {
+            return Err(MerkleError::IndexOutOfRange { index, len: size });
+        }
+
+        let (k, height, offset) = peak_of(index, size);
+        let local = index - offset;
+        let mut siblings = Vec::with_capacity(height);
+        let mut pos = leaf_position(index);
+        for h in 0..height {
+            if (local >> h) % 2 == 1 {
+                siblings.push(self.nodes[pos - subtree_nodes(h)]);
+                pos += 1;
+            } else {
+                siblings.push(self.nodes[pos + subtree_nodes(h)]);
+                pos += subtree_nodes(h) + 1;
+            }
+        }
+
+        let mut peaks = self.peaks_at(size);
+        peaks.remove(k);
+
+        Ok(MmrProof {
+            leaf_index: index,
+            mmr_size: size,
+            hasher: H::ID,
+            root,
+            siblings,
+            peaks,
+        })
+    }
+
+    /// Checks `proof` for `data` against the root the range had at the
+    /// size the proof was generated for.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if the range has not
+    /// reached that size, or if the proof is malformed, see
+    /// [`verify_mmr_proof`].
+    pub fn verify(
+        &self,
+        data: &(impl Leaf<H> + ?Sized),
+        proof: &MmrProof<H::Digest>,
+    ) -> Result<bool, MerkleError> {
+        verify_mmr_proof::<H>(&self.root_at(proof.mmr_size)?, data, proof)
+    }
+
+    /// Returns the data stored at leaf `index`, if it has been added.
+    pub fn leaf(&self, index: usize) -> Option<&T> {
+        self.data.get(index)
+    }
+
+    /// Returns the number of leaves added so far.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if no leaves have been added.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    // Returns the peaks of the range when it held `size` leaves, from
+    // left to right.
+    fn peaks_at(&self, size: usize) -> Vec<H::Digest> {
+        let mut peaks = Vec::with_capacity(size.count_ones() as usize);
+        let mut pos = 0;
+        for h in (0..usize::BITS as usize).rev() {
+            if (size >> h) % 2 == 1 {
+                pos += subtree_nodes(h);
+                peaks.push(self.nodes[pos - 1]);
+            }
+        }
+        peaks
+    }
+}
+
+impl<T: Leaf<H>, H: MerkleHasher> Default for MerkleMountainRange<T, H> {
+    fn default() -> MerkleMountainRange<T, H> {
+        MerkleMountainRange::new()
+    }
+}
+
+// Returns the number of nodes of a perfect tree of height h.
+fn subtree_nodes(h: usize) -> usize {
+    (1 << (h + 1)) - 1
+}
+
+// Returns the position in the node list of leaf `index`.
+fn leaf_position(index: usize) -> usize {
+    2 * index - index.count_ones() as usize
+}
+
+// Returns which peak of a range of `size` leaves holds leaf `index`,
+// together with that peak's height and the index of its first leaf.
+fn peak_of(index: usize, size: usize) -> (usize, usize, usize) {
+    let mut k = 0;
+    let mut offset = 0;
+    for h in (0..usize::BITS as usize).rev() {
+        if (size >> h) % 2 == 1 {
+            if index < offset + (1 << h) {
+                return (k, h, offset);
+            }
+            offset += 1 << h;
+            k += 1;
+        }
+    }
+    unreachable!("leaf index out of range")
+}
+
+// Combines the peaks into a single root from right to left.
+fn bag_peaks<H: MerkleHasher>(peaks: &[H::Digest]) -> H::Digest {
+    match peaks.split_last() {
+        None => H::hash_empty(),
+        Some((last, rest)) => rest
+            .iter()
+            .rev()
+            .fold(*last, |root, peak| H::hash_nodes(peak, &root)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LegacyHasher;
+
+    #[test]
+    fn test_positions() -> Result<(), String> {
+        let positions: Vec<usize> = (0..8).map(leaf_position).collect();
+        assert_eq!(positions, [0, 1, 3, 4, 7, 8, 10, 11]);
+
+        assert_eq!(peak_of(0, 7), (0, 2, 0));
+        assert_eq!(peak_of(5, 7), (1, 1, 4));
+        assert_eq!(peak_of(6, 7), (2, 0, 6));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_layout() -> Result<(), String> {
+        let mut mmr = MerkleMountainRange::<&str, LegacyHasher>::new();
+        for data in &["foo", "bar", "baz"] {
+            mmr.add_data(*data);
+        }
+
+        assert_eq!(mmr.nodes.len(), 4);
+        assert_eq!(mmr.nodes[2], 17075777630381501106);
+        assert_eq!(mmr.peaks(), [17075777630381501106, mmr.nodes[3]]);
+
+        Ok(())
+    }
+}
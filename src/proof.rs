@@ -0,0 +1,249 @@
+//! Membership proofs that can be checked without the tree.
+
+use crate::{HasherId, Leaf, MerkleError, MerkleHasher};
+
+/// A proof that a leaf is part of a tree with a given root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof<D> {
+    /// The position of the leaf in the tree.
+    pub leaf_index: usize,
+    /// The depth of the tree the proof was generated from.
+    pub depth: usize,
+    /// The hash function the tree was built with.
+    pub hasher: HasherId,
+    /// The root of the tree when the proof was generated. Verification
+    /// checks against a root supplied by the verifier, not this one.
+    pub root: D,
+    /// The sibling hashes on the path from the leaf up to the root,
+    /// starting with the leaf's own sibling.
+    pub siblings: Vec<D>,
+    /// For every sibling, whether it is the left (false) or right (true)
+    /// child of its parent.
+    pub directions: Vec<bool>,
+}
+
+/// Checks that `data` is the leaf at `proof.leaf_index` of a tree whose
+/// root is `root`.
+///
+/// Fails if the proof was made with a different hasher or if its
+/// siblings and directions do not describe a path of `proof.depth`.
+pub fn verify_proof<H: MerkleHasher>(
+    root: &H::Digest,
+    data: &(impl Leaf<H> + ?Sized),
+    proof: &MerkleProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    Ok(proof_root::<H>(data, proof)? == *root)
+}
+
+/// A proof that several leaves are part of a tree with a given root,
+/// sharing the siblings their paths have in common.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiProof<D> {
+    /// The positions of the leaves in ascending order, which is the order
+    /// their data must be passed to [`verify_multiproof`] in.
+    pub leaf_indices: Vec<usize>,
+    /// The depth of the tree the proof was generated from.
+    pub depth: usize,
+    /// The hash function the tree was built with.
+    pub hasher: HasherId,
+    /// The root of the tree when the proof was generated. Verification
+    /// checks against a root supplied by the verifier, not this one.
+    pub root: D,
+    /// The siblings that cannot be computed from the leaves, in the order
+    /// they are used: level by level from the leaves up, and from left to
+    /// right within a level.
+    pub siblings: Vec<D>,
+}
+
+/// Checks that `leaves` are the leaves at `proof.leaf_indices` of a tree
+/// whose root is `root`.
+///
+/// Fails if the proof was made with a different hasher, if the number of
+/// leaves does not match, or if the indices or siblings do not describe
+/// the paths of a tree of `proof.depth`.
+pub fn verify_multiproof<H: MerkleHasher, L: Leaf<H>>(
+    root: &H::Digest,
+    leaves: &[L],
+    proof: &MultiProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if leaves.is_empty() || leaves.len() != proof.leaf_indices.len() {
+        return Err(MerkleError::MalformedProof);
+    }
+    if proof.leaf_indices.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(MerkleError::MalformedProof);
+    }
+    let last = proof.leaf_indices[proof.leaf_indices.len() - 1];
+    if proof.depth < usize::BITS as usize && last >> proof.depth != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let mut nodes: Vec<(usize, H::Digest)> = proof
+        .leaf_indices
+        .iter()
+        .zip(leaves)
+        .map(|(&i, leaf)| (i, leaf.leaf_hash()))
+        .collect();
+    let mut siblings = proof.siblings.iter();
+    for _ in 0..proof.depth {
+        nodes = fold_level::<H, _>(&nodes, |_| siblings.next().copied())
+            .ok_or(MerkleError::MalformedProof)?;
+    }
+    if siblings.next().is_some() {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    Ok(nodes[0].1 == *root)
+}
+
+/// A proof that a contiguous run of leaves are exactly the leaves at
+/// positions `start..end` of a tree with a given root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeProof<D> {
+    /// The position of the first leaf of the range.
+    pub start: usize,
+    /// The position after the last leaf of the range.
+    pub end: usize,
+    /// The depth of the tree the proof was generated from.
+    pub depth: usize,
+    /// The hash function the tree was built with.
+    pub hasher: HasherId,
+    /// The root of the tree when the proof was generated. Verification
+    /// checks against a root supplied by the verifier, not this one.
+    pub root: D,
+    /// The siblings to the left of the range, bottom-up.
+    pub left: Vec<D>,
+    /// The siblings to the right of the range, bottom-up.
+    pub right: Vec<D>,
+}
+
+/// Checks that `leaves` are the leaves at positions
+/// `proof.start..proof.end` of a tree whose root is `root`.
+///
+/// Fails if the proof was made with a different hasher, if the number of
+/// leaves does not match the range, or if the boundary siblings do not
+/// describe a tree of `proof.depth`.
+pub fn verify_range_proof<H: MerkleHasher, L: Leaf<H>>(
+    root: &H::Digest,
+    leaves: &[L],
+    proof: &RangeProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if proof.start >= proof.end {
+        return Err(MerkleError::InvalidRange {
+            start: proof.start,
+            end: proof.end,
+        });
+    }
+    if leaves.len() != proof.end - proof.start {
+        return Err(MerkleError::MalformedProof);
+    }
+    if proof.depth < usize::BITS as usize && (proof.end - 1) >> proof.depth != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let mut nodes: Vec<(usize, H::Digest)> = leaves
+        .iter()
+        .enumerate()
+        .map(|(k, leaf)| (proof.start + k, leaf.leaf_hash()))
+        .collect();
+    let mut left = proof.left.iter();
+    let mut right = proof.right.iter();
+    for _ in 0..proof.depth {
+        let first = nodes[0].0;
+        nodes = fold_level::<H, _>(&nodes, |i| {
+            if i < first {
+                left.next().copied()
+            } else {
+                right.next().copied()
+            }
+        })
+        .ok_or(MerkleError::MalformedProof)?;
+    }
+    if left.next().is_some() || right.next().is_some() {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    Ok(nodes[0].1 == *root)
+}
+
+// Combines sorted, distinct (position, hash) nodes of one level into
+// their parents, asking `sibling` for the hash of every sibling that is
+// not among the nodes. Returns None if `sibling` does.
+pub(crate) fn fold_level<H: MerkleHasher, F>(
+    nodes: &[(usize, H::Digest)],
+    mut sibling: F,
+) -> Option<Vec<(usize, H::Digest)>>
+where
+    F: FnMut(usize) -> Option<H::Digest>,
+{
+    let mut parents = Vec::with_capacity(nodes.len());
+    let mut k = 0;
+    while k < nodes.len() {
+        let (i, hash) = nodes[k];
+        let parent = if i % 2 == 1 {
+            H::hash_nodes(&sibling(i - 1)?, &hash)
+        } else if k + 1 < nodes.len() && nodes[k + 1].0 == i + 1 {
+            k += 1;
+            H::hash_nodes(&hash, &nodes[k].1)
+        } else {
+            H::hash_nodes(&hash, &sibling(i + 1)?)
+        };
+        parents.push((i / 2, parent));
+        k += 1;
+    }
+    Some(parents)
+}
+
+// Returns the root `data` hashes up to along the path in `proof`.
+pub(crate) fn proof_root<H: MerkleHasher>(
+    data: &(impl Leaf<H> + ?Sized),
+    proof: &MerkleProof<H::Digest>,
+) -> Result<H::Digest, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if proof.siblings.len() != proof.depth || proof.directions.len() != proof.depth {
+        return Err(MerkleError::MalformedProof);
+    }
+    if proof.depth < usize::BITS as usize && proof.leaf_index >> proof.depth != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let mut i = proof.leaf_index;
+    for &right in &proof.directions {
+        // the sibling of a left child is on the right and vice versa
+        if right != (i % 2 == 0) {
+            return Err(MerkleError::MalformedProof);
+        }
+        i /= 2;
+    }
+
+    Ok(fold_path::<H>(
+        data.leaf_hash(),
+        &proof.siblings,
+        &proof.directions,
+    ))
+}
+
+// Hashes a leaf up to the root given its siblings from the bottom up and
+// whether each of them is a right (true) or left (false) child.
+pub(crate) fn fold_path<H: MerkleHasher>(
+    leaf: H::Digest,
+    siblings: &[H::Digest],
+    directions: &[bool],
+) -> H::Digest {
+    let mut hash = leaf;
+    for (sibling, &right) in siblings.iter().zip(directions) {
+        hash = if right {
+            H::hash_nodes(&hash, sibling)
+        } else {
+            H::hash_nodes(sibling, &hash)
+        };
+    }
+    hash
+}
@@ -0,0 +1,346 @@
+//! A Merkle tree that only stores the nodes that differ from an empty tree.
+
+use std::collections::HashMap;
+
+use crate::proof::fold_path;
+use crate::{zero_hashes, HasherId, Leaf, MerkleError, MerkleHasher, Sha256};
+
+/// A 256-bit leaf position in a [`SparseMerkleTree`], stored big-endian.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SparseKey([u8; 32]);
+
+impl SparseKey {
+    /// Creates a key from its big-endian bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> SparseKey {
+        SparseKey(bytes)
+    }
+
+    /// Returns the big-endian bytes of the key.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    // Returns true if the key addresses a leaf of a tree of depth `depth`,
+    // i.e. if it is smaller than 2^depth.
+    fn fits(&self, depth: usize) -> bool {
+        let mut zeros = 0;
+        for b in self.0.iter() {
+            if *b != 0 {
+                zeros += b.leading_zeros() as usize;
+                break;
+            }
+            zeros += 8;
+        }
+        zeros + depth >= 256
+    }
+
+    // Returns true if the node at this position is a right child.
+    fn is_right(&self) -> bool {
+        self.0[31] & 1 == 1
+    }
+
+    // Returns the position of the other child of the same parent.
+    fn sibling(&self) -> SparseKey {
+        let mut key = *self;
+        key.0[31] ^= 1;
+        key
+    }
+
+    // Returns the position of the parent one level up.
+    fn parent(&self) -> SparseKey {
+        let mut key = SparseKey::default();
+        for i in (0..32).rev() {
+            key.0[i] = self.0[i] >> 1;
+            if i > 0 {
+                key.0[i] |= self.0[i - 1] << 7;
+            }
+        }
+        key
+    }
+}
+
+impl From<u64> for SparseKey {
+    fn from(index: u64) -> SparseKey {
+        let mut key = SparseKey::default();
+        key.0[24..].copy_from_slice(&index.to_be_bytes());
+        key
+    }
+}
+
+impl From<usize> for SparseKey {
+    fn from(index: usize) -> SparseKey {
+        SparseKey::from(index as u64)
+    }
+}
+
+impl From<[u8; 32]> for SparseKey {
+    fn from(bytes: [u8; 32]) -> SparseKey {
+        SparseKey(bytes)
+    }
+}
+
+/// A proof that a leaf is at a given key of a sparse tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMerkleProof<D> {
+    /// The position of the leaf. Its low `depth` bits give the direction
+    /// of every step up to the root.
+    pub key: SparseKey,
+    /// The depth of the tree the proof was generated from.
+    pub depth: usize,
+    /// The hash function the tree was built with.
+    pub hasher: HasherId,
+    /// The root of the tree when the proof was generated.
+    pub root: D,
+    /// The sibling hashes on the path from the leaf up to the root,
+    /// starting with the leaf's own sibling.
+    pub siblings: Vec<D>,
+}
+
+/// Checks that `data` is the leaf at `proof.key` of a sparse tree whose
+/// root is `root`. An empty slot verifies against the empty leaf `""`.
+pub fn verify_sparse_proof<H: MerkleHasher>(
+    root: &H::Digest,
+    data: &(impl Leaf<H> + ?Sized),
+    proof: &SparseMerkleProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if proof.depth > 256 || proof.siblings.len() != proof.depth || !proof.key.fits(proof.depth) {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    // a right child has its sibling on the left
+    let mut directions = Vec::with_capacity(proof.depth);
+    let mut pos = proof.key;
+    for _ in 0..proof.depth {
+        directions.push(!pos.is_right());
+        pos = pos.parent();
+    }
+
+    let hash = fold_path::<H>(data.leaf_hash(), &proof.siblings, &directions);
+
+    Ok(hash == *root)
+}
+
+/// A Merkle tree of up to 2^256 leaves addressed by [`SparseKey`].
+///
+/// Empty subtrees are not stored; their hashes come from a per-level table
+/// of [`zero_hashes`]. Memory is therefore proportional to the number of
+/// leaves times the depth, and a sparse tree holding leaves `0..n` has the
+/// same root as a [`MerkleTree`](crate::MerkleTree) of the same depth that
+/// `n` leaves were appended to.
+#[derive(Debug)]
+pub struct SparseMerkleTree<T, H: MerkleHasher = Sha256> {
+    depth: usize,
+    // zero[h] is the hash of an empty subtree of height h
+    zero: Vec<H::Digest>,
+    // non-empty nodes by depth and position within that level
+    nodes: HashMap<(usize, SparseKey), H::Digest>,
+    data: HashMap<SparseKey, T>,
+}
+
+impl<T: Leaf<H>, H: MerkleHasher> SparseMerkleTree<T, H> {
+    /// Creates an empty tree of the given depth.
+    ///
+    /// Panics if `depth` is larger than 256.
+    pub fn new(depth: usize) -> SparseMerkleTree<T, H> {
+        assert!(depth <= 256, "depth must be at most 256");
+
+        SparseMerkleTree {
+            depth,
+            zero: zero_hashes::<H>(depth),
+            nodes: HashMap::new(),
+            data: HashMap::new(),
+        }
+    }
+
+    /// Stores `data` at `key`, returning the data previously stored there.
+    /// Data that hashes like an empty leaf, such as an empty string, leaves
+    /// the slot empty and is not stored, as if `key` had been removed.
+    ///
+    /// Fails with [`MerkleError::KeyOutOfRange`] if `key` is not smaller
+    /// than 2^depth.
+    pub fn insert(&mut self, key: impl Into<SparseKey>, data: T) -> Result<Option<T>, MerkleError> {
+        let key = key.into();
+        if !key.fits(self.depth) {
+            return Err(MerkleError::KeyOutOfRange);
+        }
+
+        let hash = data.leaf_hash();
+        if hash == self.zero[0] {
+            return self.remove(key);
+        }
+        self.set_leaf_hash(key, hash);
+
+        Ok(self.data.insert(key, data))
+    }
+
+    /// Empties the slot at `key`, returning the data stored there.
+    ///
+    /// Fails with [`MerkleError::KeyOutOfRange`] if `key` is not smaller
+    /// than 2^depth.
+    pub fn remove(&mut self, key: impl Into<SparseKey>) -> Result<Option<T>, MerkleError> {
+        let key = key.into();
+        if !key.fits(self.depth) {
+            return Err(MerkleError::KeyOutOfRange);
+        }
+
+        self.set_leaf_hash(key, self.zero[0]);
+
+        Ok(self.data.remove(&key))
+    }
+
+    // Stores the hash of the leaf at `key` and recomputes the path up to
+    // the root.
+    fn set_leaf_hash(&mut self, key: SparseKey, mut hash: H::Digest) {
+        let mut pos = key;
+        self.set_node(self.depth, pos, hash);
+        for d in (0..self.depth).rev() {
+            let sibling = self.node(d + 1, &pos.sibling());
+            hash = if pos.is_right() {
+                H::hash_nodes(&sibling, &hash)
+            } else {
+                H::hash_nodes(&hash, &sibling)
+            };
+            pos = pos.parent();
+            self.set_node(d, pos, hash);
+        }
+    }
+
+    /// Returns a proof for the leaf at `key` against the current root. For
+    /// an empty slot the proof shows that it holds the empty leaf.
+    ///
+    /// Fails with [`MerkleError::KeyOutOfRange`] if `key` is not smaller
+    /// than 2^depth.
+    pub fn generate_proof(
+        &self,
+        key: impl Into<SparseKey>,
+    ) -> Result<SparseMerkleProof<H::Digest>, MerkleError> {
+        let key = key.into();
+        if !key.fits(self.depth) {
+            return Err(MerkleError::KeyOutOfRange);
+        }
+
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut pos = key;
+        for d in (1..self.depth + 1).rev() {
+            siblings.push(self.node(d, &pos.sibling()));
+            pos = pos.parent();
+        }
+
+        Ok(SparseMerkleProof {
+            key,
+            depth: self.depth,
+            hasher: H::ID,
+            root: self.root(),
+            siblings,
+        })
+    }
+
+    /// Checks `proof` for `data` against the current root of the tree.
+    pub fn verify(
+        &self,
+        data: &(impl Leaf<H> + ?Sized),
+        proof: &SparseMerkleProof<H::Digest>,
+    ) -> Result<bool, MerkleError> {
+        if proof.depth != self.depth {
+            return Err(MerkleError::DepthMismatch {
+                expected: self.depth,
+                actual: proof.depth,
+            });
+        }
+
+        verify_sparse_proof::<H>(&self.root(), data, proof)
+    }
+
+    /// Returns the current root of the tree.
+    pub fn root(&self) -> H::Digest {
+        self.node(0, &SparseKey::default())
+    }
+
+    /// Returns the data stored at `key`, if any.
+    pub fn leaf(&self, key: impl Into<SparseKey>) -> Option<&T> {
+        self.data.get(&key.into())
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves stored.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if no leaves are stored.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    // Returns the hash of the node at `pos` on level `depth`.
+    fn node(&self, depth: usize, pos: &SparseKey) -> H::Digest {
+        match self.nodes.get(&(depth, *pos)) {
+            Some(hash) => *hash,
+            None => self.zero[self.depth - depth],
+        }
+    }
+
+    // Stores the hash of a node, dropping it if it equals the empty hash.
+    fn set_node(&mut self, depth: usize, pos: SparseKey, hash: H::Digest) {
+        if hash == self.zero[self.depth - depth] {
+            self.nodes.remove(&(depth, pos));
+        } else {
+            self.nodes.insert((depth, pos), hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_fits() -> Result<(), String> {
+        assert!(SparseKey::from(7u64).fits(3));
+        assert!(!SparseKey::from(8u64).fits(3));
+        assert!(SparseKey::from(0u64).fits(0));
+        assert!(!SparseKey::from(1u64).fits(0));
+        assert!(SparseKey::from_bytes([0xff; 32]).fits(256));
+        assert!(!SparseKey::from_bytes([0xff; 32]).fits(255));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_parent() -> Result<(), String> {
+        assert_eq!(SparseKey::from(6u64).parent(), SparseKey::from(3u64));
+        assert_eq!(SparseKey::from(7u64).sibling(), SparseKey::from(6u64));
+
+        let mut bytes = [0; 32];
+        bytes[30] = 1;
+        let mut half = [0; 32];
+        half[31] = 0x80;
+        assert_eq!(
+            SparseKey::from_bytes(bytes).parent(),
+            SparseKey::from_bytes(half)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_nodes_are_not_stored() -> Result<(), String> {
+        let mut mt = SparseMerkleTree::<&str, Sha256>::new(32);
+
+        mt.insert(5u64, "foo").unwrap();
+        assert_eq!(mt.nodes.len(), 33);
+
+        mt.insert(5u64, "").unwrap();
+        assert!(mt.nodes.is_empty());
+        assert_eq!(mt.root(), mt.zero[32]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+//! A Merkle tree whose nodes also commit to the sum of the balances below.
+
+use crate::{HasherId, Leaf, MerkleError, MerkleHasher, Sha256};
+
+/// A node of a [`MerkleSumTree`]: a hash and the sum of the balances of
+/// the leaves below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SumNode<D> {
+    /// The hash of the node, which commits to the sums of its children.
+    pub hash: D,
+    /// The sum of the balances of the leaves below the node.
+    pub sum: u128,
+}
+
+/// A proof that a leaf with a given balance is part of a sum tree with a
+/// given root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SumProof<D> {
+    /// The position of the leaf in the tree.
+    pub leaf_index: usize,
+    /// The depth of the tree the proof was generated from.
+    pub depth: usize,
+    /// The hash function the tree was built with.
+    pub hasher: HasherId,
+    /// The root of the tree when the proof was generated. Verification
+    /// checks against a root supplied by the verifier, not this one.
+    pub root: SumNode<D>,
+    /// The sibling nodes on the path from the leaf up to the root,
+    /// starting with the leaf's own sibling.
+    pub siblings: Vec<SumNode<D>>,
+}
+
+/// Checks that `data` with `balance` is the leaf at `proof.leaf_index` of
+/// a sum tree whose root is `root`, and that `root.sum` is the total of
+/// all balances.
+///
+/// Balances are unsigned, so no sibling can carry a negative sum to hide
+/// part of the total; fails with [`MerkleError::SumOverflow`] if the sums
+/// on the path overflow instead. Also fails if the proof was made with a
+/// different hasher or its siblings do not describe a path of
+/// `proof.depth`.
+pub fn verify_sum_proof<H: MerkleHasher>(
+    root: &SumNode<H::Digest>,
+    data: &(impl Leaf<H> + ?Sized),
+    balance: u128,
+    proof: &SumProof<H::Digest>,
+) -> Result<bool, MerkleError> {
+    if proof.hasher != H::ID {
+        return Err(MerkleError::HasherMismatch);
+    }
+    if proof.siblings.len() != proof.depth {
+        return Err(MerkleError::MalformedProof);
+    }
+    if proof.depth < usize::BITS as usize && proof.leaf_index >> proof.depth != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let mut node = SumNode {
+        hash: data.leaf_hash(),
+        sum: balance,
+    };
+    let mut i = proof.leaf_index;
+    for sibling in &proof.siblings {
+        node = if i % 2 == 1 {
+            hash_sum_nodes::<H>(sibling, &node)?
+        } else {
+            hash_sum_nodes::<H>(&node, sibling)?
+        };
+        i /= 2;
+    }
+
+    Ok(node == *root)
+}
+
+// Combines two children into their parent, whose hash commits to both
+// child hashes and both child sums.
+fn hash_sum_nodes<H: MerkleHasher>(
+    left: &SumNode<H::Digest>,
+    right: &SumNode<H::Digest>,
+) -> Result<SumNode<H::Digest>, MerkleError> {
+    let sum = left
+        .sum
+        .checked_add(right.sum)
+        .ok_or(MerkleError::SumOverflow)?;
+
+    let mut sums = left.sum.to_le_bytes().to_vec();
+    sums.extend_from_slice(&right.sum.to_le_bytes());
+    let hash = H::hash_nodes(
+        &H::hash_nodes(&left.hash, &right.hash),
+        &H::hash_leaf(&sums),
+    );
+
+    Ok(SumNode { hash, sum })
+}
+
+/// A Merkle tree of fixed depth holding up to `2^depth` leaves, each with
+/// a balance, where every node also commits to the sum of the balances
+/// below it.
+///
+/// The sum of the root is the total of all balances, e.g. the liabilities
+/// of an exchange towards its account holders, who can each check with a
+/// [`SumProof`] that their balance is counted in it.
+#[derive(Debug)]
+pub struct MerkleSumTree<T, H: MerkleHasher = Sha256> {
+    depth: usize,
+    data: Vec<(T, u128)>,
+    // tree[d][i] is node i on level d, tree[0][0] being the root
+    tree: Vec<Vec<SumNode<H::Digest>>>,
+}
+
+impl<T: Leaf<H>, H: MerkleHasher> MerkleSumTree<T, H> {
+    /// Creates a tree of the given depth whose unfilled leaves hash the
+    /// empty string and have a balance of zero.
+    pub fn new(depth: usize) -> MerkleSumTree<T, H> {
+        let mut tree = Vec::with_capacity(depth + 1);
+        tree.resize_with(depth + 1, Vec::new);
+        tree[depth].resize(
+            usize::pow(2, depth as u32),
+            SumNode {
+                hash: H::hash_leaf(b""),
+                sum: 0,
+            },
+        );
+        for d in (0..depth).rev() {
+            let empty = hash_sum_nodes::<H>(&tree[d + 1][0], &tree[d + 1][0]).unwrap();
+            tree[d].resize(usize::pow(2, d as u32), empty);
+        }
+
+        MerkleSumTree {
+            depth,
+            data: Vec::new(),
+            tree,
+        }
+    }
+
+    /// Appends `data` with `balance` as the next leaf.
+    ///
+    /// Fails with [`MerkleError::TreeFull`] if the tree holds `2^depth`
+    /// leaves, or [`MerkleError::SumOverflow`] if the total would
+    /// overflow.
+    pub fn add_data(&mut self, data: T, balance: u128) -> Result<(), MerkleError> {
+        if self.data.len() == self.capacity() {
+            return Err(MerkleError::TreeFull);
+        }
+        self.total()
+            .checked_add(balance)
+            .ok_or(MerkleError::SumOverflow)?;
+
+        let index = self.data.len();
+        self.tree[self.depth][index] = SumNode {
+            hash: data.leaf_hash(),
+            sum: balance,
+        };
+        let mut i = index;
+        for d in (0..self.depth).rev() {
+            i /= 2;
+            self.tree[d][i] =
+                hash_sum_nodes::<H>(&self.tree[d + 1][2 * i], &self.tree[d + 1][2 * i + 1])?;
+        }
+        self.data.push((data, balance));
+
+        Ok(())
+    }
+
+    /// Returns a proof of inclusion for the leaf at `index` against the
+    /// current root.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfRange`] if no leaf has been
+    /// added at `index`.
+    pub fn generate_proof(&self, index: usize) -> Result<SumProof<H::Digest>, MerkleError> {
+        if index >= self.data.len() {
+            return Err(MerkleError::IndexOutOfRange {
+                index,
+                len: self.data.len(),
+            });
+        }
+
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut i = index;
+        for d in (1..self.depth + 1).rev() {
+            siblings.push(self.tree[d][i ^ 1]);
+            i /= 2;
+        }
+
+        Ok(SumProof {
+            leaf_index: index,
+            depth: self.depth,
+            hasher: H::ID,
+            root: self.root(),
+            siblings,
+        })
+    }
+
+    /// Checks `proof` for `data` with `balance` against the current root
+    /// of the tree.
+    ///
+    /// Fails if the proof was generated for a tree of a different depth
+    /// or is otherwise malformed, see [`verify_sum_proof`].
+    pub fn verify(
+        &self,
+        data: &(impl Leaf<H> + ?Sized),
+        balance: u128,
+        proof: &SumProof<H::Digest>,
+    ) -> Result<bool, MerkleError> {
+        if proof.depth != self.depth {
+            return Err(MerkleError::DepthMismatch {
+                expected: self.depth,
+                actual: proof.depth,
+            });
+        }
+
+        verify_sum_proof::<H>(&self.root(), data, balance, proof)
+    }
+
+    /// Returns the current root of the tree.
+    pub fn root(&self) -> SumNode<H::Digest> {
+        self.tree[0][0]
+    }
+
+    /// Returns the sum of the balances of all leaves.
+    pub fn total(&self) -> u128 {
+        self.tree[0][0].sum
+    }
+
+    /// Returns the data and balance stored at leaf `index`, if it has
+    /// been added.
+    pub fn leaf(&self, index: usize) -> Option<(&T, u128)> {
+        self.data.get(index).map(|(data, balance)| (data, *balance))
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves added so far.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if no leaves have been added.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the maximum number of leaves the tree can hold.
+    pub fn capacity(&self) -> usize {
+        usize::pow(2, self.depth as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LegacyHasher;
+
+    #[test]
+    fn test_sums_are_committed() -> Result<(), String> {
+        let leaf = |sum| SumNode {
+            hash: LegacyHasher::hash_leaf(b"foo"),
+            sum,
+        };
+
+        let node = hash_sum_nodes::<LegacyHasher>(&leaf(1), &leaf(2)).unwrap();
+        assert_eq!(node.sum, 3);
+        assert_ne!(
+            hash_sum_nodes::<LegacyHasher>(&leaf(2), &leaf(1))
+                .unwrap()
+                .hash,
+            node.hash
+        );
+        assert_eq!(
+            hash_sum_nodes::<LegacyHasher>(&leaf(u128::MAX), &leaf(1)),
+            Err(MerkleError::SumOverflow)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use commonwealth::{
+    IncrementalMerkleTree, Keccak256, LegacyHasher, MerkleError, MerkleTree, Sha256,
+    SparseMerkleTree,
+};
+
+const DATA: [&str; 8] = ["foo", "bar", "baz", "yup", "maw", "wap", "pit", "fos"];
+
+#[test]
+fn test_same_roots_as_merkle_tree() -> Result<(), String> {
+    for depth in 0..4 {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(depth);
+        let mut imt = IncrementalMerkleTree::<LegacyHasher>::new(depth);
+
+        assert_eq!(imt.root(), mt.root());
+        for data in DATA.iter().take(mt.capacity()) {
+            mt.add_data(*data).unwrap();
+            imt.add_data(*data).unwrap();
+            assert_eq!(imt.root(), mt.root());
+        }
+
+        assert!(imt.is_full());
+        assert_eq!(imt.add_data("nope"), Err(MerkleError::TreeFull));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_legacy_root() -> Result<(), String> {
+    let mut imt = IncrementalMerkleTree::<LegacyHasher>::new(3);
+    for data in DATA.iter() {
+        imt.add_data(*data).unwrap();
+    }
+
+    assert_eq!(imt.len(), 8);
+    assert_eq!(imt.root(), 1556255166675498662);
+
+    Ok(())
+}
+
+#[test]
+fn test_depth_32() -> Result<(), String> {
+    let mut imt = IncrementalMerkleTree::<Keccak256>::new(32);
+    let mut smt = SparseMerkleTree::<u64, Keccak256>::new(32);
+
+    assert!(imt.is_empty());
+    for i in 0..100u64 {
+        imt.add_data(&i).unwrap();
+        smt.insert(i, i).unwrap();
+    }
+
+    assert_eq!(imt.len(), 100);
+    assert!(!imt.is_full());
+    assert_eq!(imt.root(), smt.root());
+
+    Ok(())
+}
+
+#[test]
+fn test_depth_64_never_fills() -> Result<(), String> {
+    let mut imt = IncrementalMerkleTree::<Sha256>::new(64);
+    let empty = imt.root();
+
+    imt.add_data("foo").unwrap();
+
+    assert_ne!(imt.root(), empty);
+    assert!(!imt.is_full());
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_against_recent_roots() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, Sha256>::new(4);
+    let mut imt = IncrementalMerkleTree::<Sha256>::new(4);
+    imt.set_root_history_size(2);
+
+    assert!(imt.is_known_root(&mt.root()));
+    for data in &DATA[..3] {
+        mt.add_data(*data).unwrap();
+        imt.add_data(*data).unwrap();
+    }
+    let proof = mt.generate_proof(1).unwrap();
+
+    mt.add_data(DATA[3]).unwrap();
+    imt.add_data(DATA[3]).unwrap();
+    assert_eq!(imt.verify_against_any_recent_root("bar", &proof), Ok(true));
+    assert_eq!(imt.verify_against_any_recent_root("baz", &proof), Ok(false));
+
+    imt.add_data(DATA[4]).unwrap();
+    assert_eq!(imt.verify_against_any_recent_root("bar", &proof), Ok(false));
+
+    Ok(())
+}
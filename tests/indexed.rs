@@ -0,0 +1,110 @@
+use commonwealth::{
+    verify_membership, verify_non_membership, IndexedMerkleTree, Keccak256, MerkleError, Sha256,
+    SparseKey,
+};
+
+const VALUES: [u64; 5] = [40, 10, 30, 50, 20];
+
+#[test]
+fn test_membership() -> Result<(), String> {
+    let mut mt = IndexedMerkleTree::<Sha256>::new(3);
+    for (i, value) in VALUES.iter().enumerate() {
+        assert_eq!(mt.insert(*value), Ok(i + 1));
+    }
+    assert_eq!(mt.len(), 5);
+    let root = mt.root();
+
+    for value in VALUES.iter() {
+        assert!(mt.contains(*value));
+        let proof = mt.generate_membership_proof(*value).unwrap();
+        assert_eq!(verify_membership::<Sha256>(&root, *value, &proof), Ok(true));
+        assert_eq!(
+            verify_non_membership::<Sha256>(&root, *value, &proof),
+            Ok(false)
+        );
+        assert_eq!(verify_membership::<Sha256>(&root, 15u64, &proof), Ok(false));
+    }
+
+    assert_eq!(
+        mt.generate_membership_proof(15u64),
+        Err(MerkleError::KeyNotFound)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_non_membership() -> Result<(), String> {
+    let mut mt = IndexedMerkleTree::<Sha256>::new(3);
+    for value in VALUES.iter() {
+        mt.insert(*value).unwrap();
+    }
+    let root = mt.root();
+
+    for value in [1u64, 15, 35, 49, 51, u64::MAX].iter() {
+        assert!(!mt.contains(*value));
+        let proof = mt.generate_non_membership_proof(*value).unwrap();
+        assert_eq!(
+            verify_non_membership::<Sha256>(&root, *value, &proof),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_membership::<Sha256>(&root, *value, &proof),
+            Ok(false)
+        );
+    }
+
+    // the low leaf of 35 is 30, whose next value is 40
+    let proof = mt.generate_non_membership_proof(35u64).unwrap();
+    assert_eq!(proof.leaf.value, SparseKey::from(30u64));
+    assert_eq!(proof.leaf.next_value, SparseKey::from(40u64));
+    assert_eq!(
+        verify_non_membership::<Sha256>(&root, 45u64, &proof),
+        Ok(false)
+    );
+
+    // 51 is past the largest value
+    let proof = mt.generate_non_membership_proof(51u64).unwrap();
+    assert_eq!(proof.leaf.value, SparseKey::from(50u64));
+    assert_eq!(proof.leaf.next_index, 0);
+
+    assert_eq!(
+        mt.generate_non_membership_proof(30u64),
+        Err(MerkleError::KeyExists)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_stale_low_leaf() -> Result<(), String> {
+    let mut mt = IndexedMerkleTree::<Keccak256>::new(3);
+    mt.insert(10u64).unwrap();
+    mt.insert(30u64).unwrap();
+
+    let proof = mt.generate_non_membership_proof(20u64).unwrap();
+    mt.insert(20u64).unwrap();
+
+    // the low leaf has been updated to point to 20 since
+    assert_eq!(
+        verify_non_membership::<Keccak256>(&mt.root(), 20u64, &proof),
+        Ok(false)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_insert_errors() -> Result<(), String> {
+    let mut mt = IndexedMerkleTree::<Sha256>::new(2);
+
+    assert_eq!(mt.insert(0u64), Err(MerkleError::KeyExists));
+    for value in VALUES.iter().take(3) {
+        mt.insert(*value).unwrap();
+    }
+    assert_eq!(mt.insert(40u64), Err(MerkleError::KeyExists));
+    assert_eq!(mt.insert(60u64), Err(MerkleError::TreeFull));
+    assert!(!mt.contains(60u64));
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use commonwealth::{
+    verify_audit_proof, verify_consistency, Keccak256, LegacyHasher, MerkleError, MerkleHasher,
+    MerkleLog, MerkleTree, Sha256,
+};
+
+const DATA: [&str; 8] = ["foo", "bar", "baz", "yup", "maw", "wap", "pit", "fos"];
+
+// the test vectors of the Certificate Transparency reference implementation
+const CT_LEAVES: [&[u8]; 8] = [
+    b"",
+    b"\x00",
+    b"\x10",
+    b"\x20\x21",
+    b"\x30\x31",
+    b"\x40\x41\x42\x43",
+    b"\x50\x51\x52\x53\x54\x55\x56\x57",
+    b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+];
+
+const CT_ROOTS: [&str; 8] = [
+    "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+    "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+    "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+    "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+    "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+    "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+    "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+];
+
+fn to_hex(digest: &[u8; 32]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn test_certificate_transparency_roots() -> Result<(), String> {
+    let mut log = MerkleLog::<&[u8], Sha256>::new();
+
+    assert_eq!(
+        to_hex(&log.root()),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    for (leaf, root) in CT_LEAVES.iter().zip(CT_ROOTS.iter()) {
+        log.add_data(*leaf);
+        assert_eq!(to_hex(&log.root()), *root);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_certificate_transparency_audit_path() -> Result<(), String> {
+    let mut log = MerkleLog::<&[u8], Sha256>::new();
+    for leaf in CT_LEAVES.iter().take(5) {
+        log.add_data(*leaf);
+    }
+
+    // PATH(4, D[5]) is MTH(D[0:4]) alone since the last leaf has no
+    // sibling below the root
+    let proof = log.generate_proof(4).unwrap();
+    assert_eq!(proof.tree_size, 5);
+    assert_eq!(proof.siblings.len(), 1);
+    assert_eq!(to_hex(&proof.siblings[0]), CT_ROOTS[3]);
+
+    let proof = log.generate_proof(0).unwrap();
+    assert_eq!(proof.siblings.len(), 3);
+    assert_eq!(proof.siblings[0], Sha256::hash_leaf(CT_LEAVES[1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_certificate_transparency_consistency() -> Result<(), String> {
+    let mut log = MerkleLog::<&[u8], Sha256>::new();
+    for leaf in CT_LEAVES.iter() {
+        log.add_data(*leaf);
+    }
+
+    let vectors: [(usize, usize, &[&str]); 4] = [
+        (1, 1, &[]),
+        (
+            1,
+            8,
+            &[
+                "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+                "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+            ],
+        ),
+        (
+            6,
+            8,
+            &[
+                "0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a",
+                "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+                "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            ],
+        ),
+        (
+            2,
+            5,
+            &[
+                "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+            ],
+        ),
+    ];
+    for (old_size, new_size, path) in vectors.iter() {
+        let proof = log.consistency_proof(*old_size, *new_size).unwrap();
+        let hex: Vec<String> = proof.path.iter().map(to_hex).collect();
+        assert_eq!(hex, *path);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_consistency_for_every_size() -> Result<(), String> {
+    let mut log = MerkleLog::<&str, Sha256>::new();
+    let mut roots = vec![log.root()];
+    for data in DATA.iter() {
+        log.add_data(*data);
+        roots.push(log.root());
+    }
+
+    for old_size in 0..roots.len() {
+        for new_size in old_size..roots.len() {
+            let proof = log.consistency_proof(old_size, new_size).unwrap();
+            assert_eq!(
+                verify_consistency::<Sha256>(
+                    &roots[old_size],
+                    &roots[new_size],
+                    old_size,
+                    new_size,
+                    &proof
+                ),
+                Ok(true)
+            );
+            if old_size > 0 && old_size != new_size {
+                assert_eq!(
+                    verify_consistency::<Sha256>(
+                        &roots[old_size - 1],
+                        &roots[new_size],
+                        old_size,
+                        new_size,
+                        &proof
+                    ),
+                    Ok(false)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_malformed_consistency_proof() -> Result<(), String> {
+    let mut log = MerkleLog::<&str, Sha256>::new();
+    let mut roots = vec![log.root()];
+    for data in DATA.iter() {
+        log.add_data(*data);
+        roots.push(log.root());
+    }
+
+    let mut proof = log.consistency_proof(3, 7).unwrap();
+    proof.path.pop();
+    assert_eq!(
+        verify_consistency::<Sha256>(&roots[3], &roots[7], 3, 7, &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut proof = log.consistency_proof(4, 8).unwrap();
+    proof.path.push(roots[8]);
+    assert_eq!(
+        verify_consistency::<Sha256>(&roots[4], &roots[8], 4, 8, &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let proof = log.consistency_proof(3, 7).unwrap();
+    assert_eq!(
+        verify_consistency::<Sha256>(&roots[3], &roots[7], 3, 3, &proof),
+        Err(MerkleError::MalformedProof)
+    );
+    assert_eq!(
+        verify_consistency::<Sha256>(&roots[7], &roots[3], 7, 3, &proof),
+        Err(MerkleError::InvalidRange { start: 7, end: 3 })
+    );
+    assert_eq!(
+        verify_consistency::<Keccak256>(&[0; 32], &[0; 32], 3, 7, &proof),
+        Err(MerkleError::HasherMismatch)
+    );
+    assert_eq!(
+        log.consistency_proof(3, 9),
+        Err(MerkleError::IndexOutOfRange { index: 9, len: 8 })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_power_of_two_matches_merkle_tree() -> Result<(), String> {
+    for depth in 0..4 {
+        let mut mt = MerkleTree::<&str, LegacyHasher>::new(depth);
+        let mut log = MerkleLog::<&str, LegacyHasher>::new();
+        for data in DATA.iter().take(mt.capacity()) {
+            mt.add_data(*data).unwrap();
+            log.add_data(*data);
+        }
+
+        assert_eq!(log.root(), mt.root());
+        for i in 0..log.len() {
+            assert_eq!(
+                log.generate_proof(i).unwrap().siblings,
+                mt.generate_proof(i).unwrap().siblings
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_proofs_for_every_size() -> Result<(), String> {
+    let mut log = MerkleLog::<&str, Sha256>::new();
+
+    for (n, data) in DATA.iter().enumerate() {
+        log.add_data(*data);
+        for (i, data) in DATA.iter().enumerate().take(n + 1) {
+            let proof = log.generate_proof(i).unwrap();
+            assert_eq!(log.verify(*data, &proof), Ok(true));
+            assert_eq!(log.verify("nope", &proof), Ok(false));
+        }
+    }
+
+    assert_eq!(
+        log.generate_proof(8),
+        Err(MerkleError::IndexOutOfRange { index: 8, len: 8 })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_malformed_audit_proof() -> Result<(), String> {
+    let mut log = MerkleLog::<&str, Sha256>::new();
+    for data in DATA.iter().take(5) {
+        log.add_data(*data);
+    }
+    let root = log.root();
+
+    let mut proof = log.generate_proof(2).unwrap();
+    proof.siblings.pop();
+    assert_eq!(
+        verify_audit_proof::<Sha256>(&root, "baz", &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut proof = log.generate_proof(4).unwrap();
+    proof.siblings.push(root);
+    assert_eq!(
+        verify_audit_proof::<Sha256>(&root, "maw", &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut proof = log.generate_proof(4).unwrap();
+    proof.leaf_index = 5;
+    assert_eq!(
+        verify_audit_proof::<Sha256>(&root, "maw", &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use commonwealth::{verify_map_proof, Keccak256, MerkleError, Sha256, SparseMerkleMap};
+
+const ACCOUNTS: [(&str, u128); 4] = [("alice", 10), ("bob", 20), ("carol", 0), ("dave", 40)];
+
+#[test]
+fn test_insert_update_remove() -> Result<(), String> {
+    let mut map = SparseMerkleMap::<String, u128, Sha256>::new();
+    let empty_root = map.root();
+
+    for (key, value) in ACCOUNTS.iter() {
+        assert_eq!(map.insert(key.to_string(), *value), None);
+    }
+    assert_eq!(map.len(), 4);
+    assert_eq!(map.get("bob"), Some(&20));
+    assert_eq!(map.get("eve"), None);
+    assert!(map.contains_key("carol"));
+
+    let root = map.root();
+    assert_eq!(map.insert("bob".to_string(), 25), Some(20));
+    assert_ne!(map.root(), root);
+    assert_eq!(map.insert("bob".to_string(), 20), Some(25));
+    assert_eq!(map.root(), root);
+
+    for (key, value) in ACCOUNTS.iter() {
+        assert_eq!(map.remove(*key), Some(*value));
+    }
+    assert_eq!(map.remove("alice"), None);
+    assert!(map.is_empty());
+    assert_eq!(map.root(), empty_root);
+
+    Ok(())
+}
+
+#[test]
+fn test_insertion_order_does_not_matter() -> Result<(), String> {
+    let mut map = SparseMerkleMap::<&str, u128, Keccak256>::new();
+    let mut reversed = SparseMerkleMap::<&str, u128, Keccak256>::new();
+    for (key, value) in ACCOUNTS.iter() {
+        map.insert(*key, *value);
+    }
+    for (key, value) in ACCOUNTS.iter().rev() {
+        reversed.insert(*key, *value);
+    }
+
+    assert_eq!(map.root(), reversed.root());
+
+    Ok(())
+}
+
+#[test]
+fn test_presence_and_absence_proofs() -> Result<(), String> {
+    let mut map = SparseMerkleMap::<&str, u128, Sha256>::new();
+    for (key, value) in ACCOUNTS.iter() {
+        map.insert(*key, *value);
+    }
+    let root = map.root();
+
+    for (key, value) in ACCOUNTS.iter() {
+        let (found, proof) = map.get_with_proof(*key);
+        assert_eq!(found, Some(value));
+        // four keys make for a handful of non-empty siblings out of 256
+        assert!(proof.siblings.len() < 8);
+        assert_eq!(
+            verify_map_proof::<Sha256>(&root, *key, Some(value), &proof),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_map_proof::<Sha256>(&root, *key, Some(&1u128), &proof),
+            Ok(false)
+        );
+        assert_eq!(
+            verify_map_proof::<Sha256>(&root, *key, None::<&u128>, &proof),
+            Ok(false)
+        );
+    }
+
+    let (found, proof) = map.get_with_proof("eve");
+    assert_eq!(found, None);
+    assert_eq!(
+        verify_map_proof::<Sha256>(&root, "eve", None::<&u128>, &proof),
+        Ok(true)
+    );
+    assert_eq!(
+        verify_map_proof::<Sha256>(&root, "eve", Some(&0u128), &proof),
+        Ok(false)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_malformed_map_proof() -> Result<(), String> {
+    let mut map = SparseMerkleMap::<&str, u128, Sha256>::new();
+    map.insert("alice", 10);
+    map.insert("bob", 20);
+    let root = map.root();
+
+    let (_, mut proof) = map.get_with_proof("alice");
+    proof.siblings.push(root);
+    assert_eq!(
+        verify_map_proof::<Sha256>(&root, "alice", Some(&10u128), &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let (_, proof) = map.get_with_proof("alice");
+    assert_eq!(
+        verify_map_proof::<Keccak256>(&root, "alice", Some(&10u128), &proof),
+        Err(MerkleError::HasherMismatch)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,602 @@
+use commonwealth::{
+    verify_multiproof, verify_proof, verify_range_proof, Blake2b256, GrowthPolicy, Hash256,
+    HasherId, Keccak256, Leaf, LegacyHasher, MerkleError, MerkleHasher, MerkleTree, PreHashed,
+    RootMismatch, Sha256, TreeFormat,
+};
+
+const DATA: [&str; 8] = ["foo", "bar", "baz", "yup", "maw", "wap", "pit", "fos"];
+
+fn full_tree(depth: usize) -> MerkleTree<&'static str, LegacyHasher> {
+    let mut mt = MerkleTree::<&str, LegacyHasher>::new(depth);
+    for data in DATA.iter().take(mt.capacity()) {
+        mt.add_data(*data).unwrap();
+    }
+    mt
+}
+
+#[test]
+fn test_new_tree_is_empty() -> Result<(), String> {
+    let mt = MerkleTree::<&str, LegacyHasher>::new(3);
+
+    assert!(mt.is_empty());
+    assert_eq!(mt.len(), 0);
+    assert_eq!(mt.depth(), 3);
+    assert_eq!(mt.capacity(), 8);
+    assert_eq!(mt.expected_root(), None);
+    assert_eq!(mt.leaf(0), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_add_data_and_leaf_access() -> Result<(), String> {
+    let mt = full_tree(3);
+
+    assert_eq!(mt.len(), 8);
+    for (i, data) in DATA.iter().enumerate() {
+        assert_eq!(mt.leaf(i), Some(data));
+    }
+    assert_eq!(mt.leaf(8), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_add_data_when_full() -> Result<(), String> {
+    let mut mt = full_tree(1);
+
+    assert_eq!(mt.add_data("baz"), Err(MerkleError::TreeFull));
+
+    assert_eq!(mt.len(), 2);
+    assert_eq!(mt.leaf(2), None);
+
+    Ok(())
+}
+
+#[test]
+fn test_proof_round_trip() -> Result<(), String> {
+    let mt = full_tree(3);
+
+    for i in 0..mt.len() {
+        let proof = mt.generate_proof(i).unwrap();
+        assert_eq!(proof.leaf_index, i);
+        assert_eq!(proof.depth, mt.depth());
+        assert_eq!(proof.hasher, HasherId::Legacy);
+        assert_eq!(proof.root, mt.root());
+        assert_eq!(proof.siblings.len(), mt.depth());
+        assert_eq!(mt.verify(mt.leaf(i).unwrap(), &proof), Ok(true));
+        assert_eq!(mt.verify("nope", &proof), Ok(false));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_proof_for_missing_leaf() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, LegacyHasher>::new(2);
+    mt.add_data("foo").unwrap();
+
+    assert_eq!(
+        mt.generate_proof(1),
+        Err(MerkleError::IndexOutOfRange { index: 1, len: 1 })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_malformed_proof() -> Result<(), String> {
+    let mt = full_tree(2);
+    let proof = mt.generate_proof(0).unwrap();
+
+    let mut truncated = proof.clone();
+    truncated.siblings.pop();
+    assert_eq!(
+        mt.verify("foo", &truncated),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut flipped = proof.clone();
+    flipped.directions[0] = false;
+    assert_eq!(mt.verify("foo", &flipped), Err(MerkleError::MalformedProof));
+
+    let mut out_of_range = proof.clone();
+    out_of_range.leaf_index = 4;
+    assert_eq!(
+        mt.verify("foo", &out_of_range),
+        Err(MerkleError::MalformedProof)
+    );
+
+    assert_eq!(
+        full_tree(1).verify("foo", &proof),
+        Err(MerkleError::DepthMismatch {
+            expected: 1,
+            actual: 2
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_legacy_hashes() -> Result<(), String> {
+    assert_eq!(LegacyHasher::hash_leaf(b"foo"), 4506850079084802999);
+    assert_eq!(LegacyHasher::hash_leaf(b"bar"), 3676438629107045207);
+    assert_eq!(
+        LegacyHasher::hash_nodes(&4506850079084802999, &3676438629107045207),
+        17075777630381501106
+    );
+
+    Ok(())
+}
+
+fn check_hasher<H: MerkleHasher>() {
+    // compute the expected root of a full depth 2 tree by hand
+    let leaves: Vec<H::Digest> = DATA[..4]
+        .iter()
+        .map(|data| H::hash_leaf(data.as_bytes()))
+        .collect();
+    let root = H::hash_nodes(
+        &H::hash_nodes(&leaves[0], &leaves[1]),
+        &H::hash_nodes(&leaves[2], &leaves[3]),
+    );
+
+    let mut mt = MerkleTree::<&str, H>::new(2);
+    for data in &DATA[..4] {
+        mt.add_data(*data).unwrap();
+    }
+    assert_eq!(mt.root(), root);
+
+    for i in 0..mt.len() {
+        let proof = mt.generate_proof(i).unwrap();
+        assert_eq!(mt.verify(DATA[i], &proof), Ok(true));
+        assert_eq!(mt.verify(DATA[(i + 1) % 4], &proof), Ok(false));
+    }
+}
+
+#[test]
+fn test_sha256_tree() -> Result<(), String> {
+    check_hasher::<Sha256>();
+
+    Ok(())
+}
+
+#[test]
+fn test_keccak256_tree() -> Result<(), String> {
+    check_hasher::<Keccak256>();
+
+    Ok(())
+}
+
+#[test]
+fn test_blake2b256_tree() -> Result<(), String> {
+    check_hasher::<Blake2b256>();
+
+    Ok(())
+}
+
+#[test]
+fn test_tree_format() -> Result<(), String> {
+    assert_eq!(full_tree(1).format(), TreeFormat::V0);
+    assert_eq!(MerkleTree::<&str, Sha256>::new(1).format(), TreeFormat::V1);
+
+    Ok(())
+}
+
+#[test]
+fn test_root_is_current_after_every_append() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, LegacyHasher>::new(1);
+    let empty = MerkleTree::<&str, LegacyHasher>::new(1).root();
+
+    mt.add_data("foo").unwrap();
+    assert_ne!(mt.root(), empty);
+    assert_eq!(
+        mt.root(),
+        LegacyHasher::hash_nodes(
+            &LegacyHasher::hash_leaf(b"foo"),
+            &LegacyHasher::hash_leaf(b"")
+        )
+    );
+    assert_eq!(mt.verify("foo", &mt.generate_proof(0).unwrap()), Ok(true));
+
+    mt.add_data("bar").unwrap();
+    assert_eq!(mt.root(), 17075777630381501106);
+
+    Ok(())
+}
+
+#[test]
+fn test_stale_proof_does_not_verify() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, LegacyHasher>::new(1);
+
+    mt.add_data("foo").unwrap();
+    let proof = mt.generate_proof(0).unwrap();
+    mt.add_data("bar").unwrap();
+
+    assert_ne!(proof.root, mt.root());
+    assert_eq!(mt.verify("foo", &proof), Ok(false));
+    assert_eq!(mt.verify("foo", &mt.generate_proof(0).unwrap()), Ok(true));
+
+    Ok(())
+}
+
+#[test]
+fn test_expected_root() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, LegacyHasher>::with_expected_root(1, 17075777630381501106);
+    mt.add_data("foo").unwrap();
+    mt.add_data("bar").unwrap();
+
+    assert_eq!(mt.expected_root(), Some(17075777630381501106));
+    assert_eq!(mt.check_root(), Ok(()));
+
+    let mut mt = MerkleTree::<&str, LegacyHasher>::with_expected_root(1, 17075777630381501106);
+    mt.add_data("bar").unwrap();
+    mt.add_data("foo").unwrap();
+
+    assert_eq!(
+        mt.check_root(),
+        Err(RootMismatch {
+            expected: 17075777630381501106,
+            actual: mt.root(),
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_proof_without_tree() -> Result<(), String> {
+    let mt = full_tree(3);
+    let root = mt.root();
+    let proofs: Vec<_> = (0..mt.len())
+        .map(|i| mt.generate_proof(i).unwrap())
+        .collect();
+    drop(mt);
+
+    for (i, proof) in proofs.iter().enumerate() {
+        assert_eq!(
+            verify_proof::<LegacyHasher>(&root, DATA[i], proof),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_proof::<LegacyHasher>(&root, DATA[(i + 1) % 8], proof),
+            Ok(false)
+        );
+        assert_eq!(verify_proof::<LegacyHasher>(&0, DATA[i], proof), Ok(false));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_proof_hasher_mismatch() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, Sha256>::new(1);
+    mt.add_data("foo").unwrap();
+    let proof = mt.generate_proof(0).unwrap();
+
+    assert_eq!(
+        verify_proof::<Keccak256>(&mt.root(), "foo", &proof),
+        Err(MerkleError::HasherMismatch)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generic_leaves() -> Result<(), String> {
+    let mut strings = MerkleTree::<String, Sha256>::new(1);
+    strings.add_data(String::from("foo")).unwrap();
+    strings.add_data(String::from("bar")).unwrap();
+
+    let mut hashes = MerkleTree::<PreHashed<Hash256>, Sha256>::new(1);
+    for data in &["foo", "bar"] {
+        let hash = Leaf::<Sha256>::leaf_hash(*data);
+        hashes.add_data(PreHashed(hash)).unwrap();
+    }
+
+    assert_eq!(strings.root(), hashes.root());
+    assert_eq!(strings.leaf(1).map(String::as_str), Some("bar"));
+    let proof = strings.generate_proof(0).unwrap();
+    assert_eq!(strings.verify("foo", &proof), Ok(true));
+    assert_eq!(hashes.verify(hashes.leaf(0).unwrap(), &proof), Ok(true));
+
+    Ok(())
+}
+
+#[test]
+fn test_integer_leaves() -> Result<(), String> {
+    let mut mt = MerkleTree::<u128, Sha256>::new(2);
+    for balance in &[10u128, 20, 30] {
+        mt.add_data(*balance).unwrap();
+    }
+
+    assert_eq!(mt.leaf(2), Some(&30));
+    assert_eq!(mt.verify(&20u128, &mt.generate_proof(1).unwrap()), Ok(true));
+    assert_eq!(
+        mt.verify(&21u128, &mt.generate_proof(1).unwrap()),
+        Ok(false)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_against_recent_roots() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, Sha256>::new(3);
+    mt.set_root_history_size(3);
+
+    mt.add_data("foo").unwrap();
+    let proof = mt.generate_proof(0).unwrap();
+    let old_root = mt.root();
+
+    mt.add_data("bar").unwrap();
+    assert_eq!(mt.verify("foo", &proof), Ok(false));
+    assert!(mt.is_known_root(&old_root));
+    assert_eq!(mt.verify_against_any_recent_root("foo", &proof), Ok(true));
+    assert_eq!(mt.verify_against_any_recent_root("bar", &proof), Ok(false));
+
+    // the history holds the empty root and the roots after each insertion
+    mt.add_data("baz").unwrap();
+    assert_eq!(mt.root_history().len(), 3);
+    assert_eq!(mt.verify_against_any_recent_root("foo", &proof), Ok(true));
+
+    mt.add_data("yup").unwrap();
+    assert!(!mt.is_known_root(&old_root));
+    assert_eq!(mt.verify_against_any_recent_root("foo", &proof), Ok(false));
+    assert!(mt.is_known_root(&mt.root()));
+
+    Ok(())
+}
+
+#[test]
+fn test_update_and_clear_leaf() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, Sha256>::new(3);
+    for data in DATA.iter() {
+        mt.add_data(*data).unwrap();
+    }
+    let original_root = mt.root();
+
+    let (old_root, new_root) = mt.update_leaf(5, "new").unwrap();
+    assert_ne!(old_root, new_root);
+    assert_eq!(mt.root(), new_root);
+    assert!(mt.is_known_root(&old_root));
+
+    let proof = mt.generate_proof(5).unwrap();
+    assert_eq!(mt.verify("new", &proof), Ok(true));
+    assert_eq!(mt.verify("wap", &proof), Ok(false));
+
+    let mut expected = MerkleTree::<&str, Sha256>::new(3);
+    for (i, data) in DATA.iter().enumerate() {
+        expected
+            .add_data(if i == 5 { "new" } else { *data })
+            .unwrap();
+    }
+    assert_eq!(mt.root(), expected.root());
+
+    let (_, cleared_root) = mt.clear_leaf(5).unwrap();
+    assert_eq!(mt.leaf(5), None);
+    assert_eq!(mt.verify("", &mt.generate_proof(5).unwrap()), Ok(true));
+    assert_eq!(mt.update_leaf(5, "wap").unwrap().0, cleared_root);
+    assert_eq!(mt.root(), original_root);
+
+    Ok(())
+}
+
+#[test]
+fn test_growth() -> Result<(), String> {
+    let mut mt = MerkleTree::<&str, Sha256>::new(1);
+    assert_eq!(mt.growth_policy(), GrowthPolicy::Fixed);
+    mt.set_growth_policy(GrowthPolicy::Grow);
+
+    mt.add_data("foo").unwrap();
+    let stale = mt.generate_proof(0).unwrap();
+    mt.add_data("bar").unwrap();
+    let proof = mt.generate_proof(0).unwrap();
+    let mut forged = proof.clone();
+    forged.siblings[0] = Sha256::hash_leaf(b"forged");
+
+    for data in DATA.iter().skip(2) {
+        mt.add_data(*data).unwrap();
+    }
+    mt.add_data("new").unwrap();
+
+    assert_eq!(mt.depth(), 4);
+    assert_eq!(mt.capacity(), 16);
+    assert_eq!(mt.len(), 9);
+
+    let mut expected = MerkleTree::<&str, Sha256>::new(4);
+    for data in DATA.iter() {
+        expected.add_data(*data).unwrap();
+    }
+    expected.add_data("new").unwrap();
+    assert_eq!(mt.root(), expected.root());
+
+    assert_eq!(
+        mt.verify("foo", &proof),
+        Err(MerkleError::DepthMismatch {
+            expected: 4,
+            actual: 1
+        })
+    );
+    let upgraded = mt.upgrade_proof(&proof).unwrap();
+    assert_eq!(upgraded, mt.generate_proof(0).unwrap());
+    assert_eq!(mt.verify("foo", &upgraded), Ok(true));
+
+    // siblings come from the tree, not from the proof being upgraded
+    assert_eq!(mt.upgrade_proof(&stale), Ok(upgraded.clone()));
+    assert_eq!(mt.upgrade_proof(&forged), Ok(upgraded.clone()));
+
+    let mut outside = proof.clone();
+    outside.leaf_index = 2;
+    assert_eq!(mt.upgrade_proof(&outside), Err(MerkleError::MalformedProof));
+
+    assert_eq!(
+        mt.upgrade_proof(&expected.generate_proof(8).unwrap()),
+        Ok(expected.generate_proof(8).unwrap())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_multiproof_matches_single_proofs() -> Result<(), String> {
+    let mt = full_tree(3);
+
+    for (i, data) in DATA.iter().enumerate() {
+        let proof = mt.generate_multiproof(&[i]).unwrap();
+        assert_eq!(proof.siblings, mt.generate_proof(i).unwrap().siblings);
+        assert_eq!(
+            verify_multiproof::<LegacyHasher, _>(&mt.root(), &[*data], &proof),
+            Ok(true)
+        );
+    }
+
+    // the siblings of 1 and 2 near the root are only included once, and
+    // those of 0 and 3 are computed from the leaves
+    let proof = mt.generate_multiproof(&[6, 1, 2, 1]).unwrap();
+    assert_eq!(proof.leaf_indices, [1, 2, 6]);
+    let single: Vec<_> = [1, 2, 6]
+        .iter()
+        .flat_map(|&i| mt.generate_proof(i).unwrap().siblings)
+        .collect();
+    assert_eq!(single.len(), 9);
+    assert_eq!(proof.siblings.len(), 4);
+    assert!(proof.siblings.iter().all(|s| single.contains(s)));
+
+    Ok(())
+}
+
+#[test]
+fn test_multiproof_every_subset() -> Result<(), String> {
+    let mt = full_tree(3);
+
+    for set in 1..256usize {
+        let indices: Vec<usize> = (0..8).filter(|i| set >> i & 1 == 1).collect();
+        let leaves: Vec<&str> = indices.iter().map(|&i| DATA[i]).collect();
+        let proof = mt.generate_multiproof(&indices).unwrap();
+
+        assert_eq!(
+            verify_multiproof::<LegacyHasher, _>(&mt.root(), &leaves, &proof),
+            Ok(true)
+        );
+        let mut wrong = leaves.clone();
+        wrong[0] = "nope";
+        assert_eq!(
+            verify_multiproof::<LegacyHasher, _>(&mt.root(), &wrong, &proof),
+            Ok(false)
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_malformed_multiproof() -> Result<(), String> {
+    let mt = full_tree(3);
+    let root = mt.root();
+
+    assert_eq!(
+        mt.generate_multiproof(&[]),
+        Err(MerkleError::MalformedProof)
+    );
+    assert_eq!(
+        mt.generate_multiproof(&[0, 8]),
+        Err(MerkleError::IndexOutOfRange { index: 8, len: 8 })
+    );
+
+    let proof = mt.generate_multiproof(&[0, 5]).unwrap();
+    assert_eq!(
+        verify_multiproof::<LegacyHasher, _>(&root, &["foo"], &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut short = proof.clone();
+    short.siblings.pop();
+    assert_eq!(
+        verify_multiproof::<LegacyHasher, _>(&root, &["foo", "wap"], &short),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut long = proof.clone();
+    long.siblings.push(root);
+    assert_eq!(
+        verify_multiproof::<LegacyHasher, _>(&root, &["foo", "wap"], &long),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut unsorted = proof;
+    unsorted.leaf_indices = vec![5, 0];
+    assert_eq!(
+        verify_multiproof::<LegacyHasher, _>(&root, &["wap", "foo"], &unsorted),
+        Err(MerkleError::MalformedProof)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_range_proofs() -> Result<(), String> {
+    let mt = full_tree(3);
+
+    for start in 0..8 {
+        for end in start + 1..9 {
+            let proof = mt.generate_range_proof(start, end).unwrap();
+            let indices: Vec<usize> = (start..end).collect();
+            assert_eq!(
+                proof.left.len() + proof.right.len(),
+                mt.generate_multiproof(&indices).unwrap().siblings.len()
+            );
+            assert_eq!(
+                verify_range_proof::<LegacyHasher, _>(&mt.root(), &DATA[start..end], &proof),
+                Ok(true)
+            );
+        }
+    }
+
+    let proof = mt.generate_range_proof(1, 7).unwrap();
+    assert_eq!(proof.left, [4506850079084802999]);
+    assert_eq!(proof.right, [LegacyHasher::hash_leaf(b"fos")]);
+    assert_eq!(
+        verify_range_proof::<LegacyHasher, _>(&mt.root(), &DATA[2..8], &proof),
+        Ok(false)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_malformed_range_proof() -> Result<(), String> {
+    let mt = full_tree(3);
+    let root = mt.root();
+
+    assert_eq!(
+        mt.generate_range_proof(3, 3),
+        Err(MerkleError::InvalidRange { start: 3, end: 3 })
+    );
+    assert_eq!(
+        mt.generate_range_proof(3, 9),
+        Err(MerkleError::IndexOutOfRange { index: 8, len: 8 })
+    );
+
+    let proof = mt.generate_range_proof(1, 3).unwrap();
+    assert_eq!(
+        verify_range_proof::<LegacyHasher, _>(&root, &DATA[1..4], &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut short = proof.clone();
+    short.right.pop();
+    assert_eq!(
+        verify_range_proof::<LegacyHasher, _>(&root, &DATA[1..3], &short),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut long = proof;
+    long.left.push(root);
+    assert_eq!(
+        verify_range_proof::<LegacyHasher, _>(&root, &DATA[1..3], &long),
+        Err(MerkleError::MalformedProof)
+    );
+
+    Ok(())
+}
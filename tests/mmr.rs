@@ -0,0 +1,113 @@
+use commonwealth::{
+    verify_mmr_proof, Keccak256, LegacyHasher, MerkleError, MerkleHasher, MerkleMountainRange,
+    MerkleTree, Sha256,
+};
+
+const DATA: [&str; 8] = ["foo", "bar", "baz", "yup", "maw", "wap", "pit", "fos"];
+
+#[test]
+fn test_single_peak_matches_merkle_tree() -> Result<(), String> {
+    let mut mmr = MerkleMountainRange::<&str, LegacyHasher>::new();
+    for data in DATA.iter() {
+        mmr.add_data(*data);
+    }
+
+    assert_eq!(mmr.peaks(), [1556255166675498662]);
+    assert_eq!(mmr.root(), 1556255166675498662);
+
+    let mut mt = MerkleTree::<&str, LegacyHasher>::new(3);
+    for data in DATA.iter() {
+        mt.add_data(*data).unwrap();
+    }
+    for i in 0..DATA.len() {
+        assert_eq!(
+            mmr.generate_proof(i).unwrap().siblings,
+            mt.generate_proof(i).unwrap().siblings
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bagging() -> Result<(), String> {
+    let mut mmr = MerkleMountainRange::<&str, Sha256>::new();
+    assert_eq!(mmr.root(), Sha256::hash_empty());
+
+    for data in DATA.iter().take(7) {
+        mmr.add_data(*data);
+    }
+
+    let peaks = mmr.peaks();
+    assert_eq!(peaks.len(), 3);
+    assert_eq!(peaks[2], Sha256::hash_leaf(b"pit"));
+    assert_eq!(
+        mmr.root(),
+        Sha256::hash_nodes(&peaks[0], &Sha256::hash_nodes(&peaks[1], &peaks[2]))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_proofs_against_every_size() -> Result<(), String> {
+    let mut mmr = MerkleMountainRange::<&str, Keccak256>::new();
+    let mut roots = vec![mmr.root()];
+    for data in DATA.iter() {
+        mmr.add_data(*data);
+        roots.push(mmr.root());
+    }
+
+    for (size, root) in roots.iter().enumerate().skip(1) {
+        assert_eq!(mmr.root_at(size), Ok(*root));
+        for (i, data) in DATA.iter().enumerate().take(size) {
+            let proof = mmr.generate_proof_at(i, size).unwrap();
+            assert_eq!(verify_mmr_proof::<Keccak256>(root, *data, &proof), Ok(true));
+            assert_eq!(mmr.verify(*data, &proof), Ok(true));
+            assert_eq!(mmr.verify("nope", &proof), Ok(false));
+        }
+    }
+
+    assert_eq!(
+        mmr.generate_proof_at(3, 3),
+        Err(MerkleError::IndexOutOfRange { index: 3, len: 3 })
+    );
+    assert_eq!(
+        mmr.root_at(9),
+        Err(MerkleError::IndexOutOfRange { index: 9, len: 8 })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_malformed_mmr_proof() -> Result<(), String> {
+    let mut mmr = MerkleMountainRange::<&str, Sha256>::new();
+    for data in DATA.iter().take(6) {
+        mmr.add_data(*data);
+    }
+    let root = mmr.root();
+
+    let mut proof = mmr.generate_proof(4).unwrap();
+    proof.siblings.pop();
+    assert_eq!(
+        verify_mmr_proof::<Sha256>(&root, "maw", &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut proof = mmr.generate_proof(4).unwrap();
+    proof.peaks.push(root);
+    assert_eq!(
+        verify_mmr_proof::<Sha256>(&root, "maw", &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    let mut proof = mmr.generate_proof(4).unwrap();
+    proof.leaf_index = 6;
+    assert_eq!(
+        verify_mmr_proof::<Sha256>(&root, "maw", &proof),
+        Err(MerkleError::MalformedProof)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use commonwealth::{
+    verify_sparse_proof, LegacyHasher, MerkleError, MerkleTree, Sha256, SparseKey, SparseMerkleTree,
+};
+
+const DATA: [&str; 8] = ["foo", "bar", "baz", "yup", "maw", "wap", "pit", "fos"];
+
+#[test]
+fn test_same_roots_as_dense_tree() -> Result<(), String> {
+    let mut dense = MerkleTree::<&str, LegacyHasher>::new(3);
+    let mut sparse = SparseMerkleTree::<&str, LegacyHasher>::new(3);
+
+    assert_eq!(sparse.root(), dense.root());
+    for (i, data) in DATA.iter().enumerate() {
+        dense.add_data(*data).unwrap();
+        sparse.insert(i, *data).unwrap();
+        assert_eq!(sparse.root(), dense.root());
+    }
+    assert_eq!(sparse.root(), 1556255166675498662);
+
+    for (i, data) in DATA.iter().enumerate() {
+        let proof = sparse.generate_proof(i).unwrap();
+        assert_eq!(proof.siblings, dense.generate_proof(i).unwrap().siblings);
+        assert_eq!(sparse.verify(*data, &proof), Ok(true));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_depth_32() -> Result<(), String> {
+    let mut mt = SparseMerkleTree::<&str, Sha256>::new(32);
+    let keys = [0u64, 1, 12345, (1 << 32) - 1];
+
+    for (key, data) in keys.iter().zip(DATA.iter()) {
+        assert_eq!(mt.insert(*key, *data), Ok(None));
+    }
+    assert_eq!(mt.len(), 4);
+    assert_eq!(mt.leaf(12345u64), Some(&"baz"));
+    assert_eq!(mt.leaf(2u64), None);
+    assert_eq!(
+        mt.insert(1u64 << 32, "nope"),
+        Err(MerkleError::KeyOutOfRange)
+    );
+
+    for (key, data) in keys.iter().zip(DATA.iter()) {
+        let proof = mt.generate_proof(*key).unwrap();
+        assert_eq!(
+            verify_sparse_proof::<Sha256>(&mt.root(), *data, &proof),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_sparse_proof::<Sha256>(&mt.root(), "nope", &proof),
+            Ok(false)
+        );
+    }
+
+    // an empty slot proves to hold the empty leaf
+    let proof = mt.generate_proof(2u64).unwrap();
+    assert_eq!(mt.verify("", &proof), Ok(true));
+
+    Ok(())
+}
+
+#[test]
+fn test_depth_256_keys() -> Result<(), String> {
+    let mut mt = SparseMerkleTree::<&str, Sha256>::new(256);
+    let alice = SparseKey::from_bytes([0xaa; 32]);
+    let bob = SparseKey::from_bytes([0x0b; 32]);
+
+    mt.insert(alice, "alice").unwrap();
+    mt.insert(bob, "bob").unwrap();
+    assert_eq!(mt.insert(alice, "alice2"), Ok(Some("alice")));
+
+    let proof = mt.generate_proof(alice).unwrap();
+    assert_eq!(proof.siblings.len(), 256);
+    assert_eq!(mt.verify("alice2", &proof), Ok(true));
+    assert_eq!(mt.verify("alice", &proof), Ok(false));
+
+    let mut moved = mt.generate_proof(bob).unwrap();
+    moved.key = alice;
+    assert_eq!(mt.verify("bob", &moved), Ok(false));
+
+    Ok(())
+}
+
+#[test]
+fn test_insert_order_does_not_matter() -> Result<(), String> {
+    let mut a = SparseMerkleTree::<&str, Sha256>::new(16);
+    let mut b = SparseMerkleTree::<&str, Sha256>::new(16);
+
+    a.insert(3u64, "foo").unwrap();
+    a.insert(900u64, "bar").unwrap();
+    b.insert(900u64, "bar").unwrap();
+    b.insert(3u64, "foo").unwrap();
+
+    assert_eq!(a.root(), b.root());
+
+    Ok(())
+}
+
+#[test]
+fn test_remove() -> Result<(), String> {
+    let mut mt = SparseMerkleTree::<&str, Sha256>::new(16);
+    let empty_root = mt.root();
+
+    mt.insert(3u64, "foo").unwrap();
+    mt.insert(9u64, "bar").unwrap();
+    assert_eq!(mt.remove(3u64), Ok(Some("foo")));
+    assert_eq!(mt.remove(3u64), Ok(None));
+    assert_eq!(mt.leaf(3u64), None);
+    assert_eq!(mt.len(), 1);
+    assert_eq!(mt.remove(1u64 << 16), Err(MerkleError::KeyOutOfRange));
+
+    assert_eq!(mt.remove(9u64), Ok(Some("bar")));
+    assert_eq!(mt.root(), empty_root);
+
+    // data hashing like an empty leaf empties the slot instead
+    mt.insert(5u64, "baz").unwrap();
+    assert_eq!(mt.insert(5u64, ""), Ok(Some("baz")));
+    assert_eq!(mt.leaf(5u64), None);
+    assert!(mt.is_empty());
+    assert_eq!(mt.root(), empty_root);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use commonwealth::{verify_sum_proof, Blake2b256, MerkleError, MerkleSumTree, Sha256, SumNode};
+
+const ACCOUNTS: [(&str, u128); 5] = [
+    ("alice", 10),
+    ("bob", 20),
+    ("carol", 0),
+    ("dave", 40),
+    ("erin", 5),
+];
+
+#[test]
+fn test_total_liabilities() -> Result<(), String> {
+    let mut mt = MerkleSumTree::<&str, Sha256>::new(3);
+    assert_eq!(mt.total(), 0);
+
+    for (data, balance) in ACCOUNTS.iter() {
+        mt.add_data(*data, *balance).unwrap();
+    }
+    assert_eq!(mt.total(), 75);
+    assert_eq!(mt.root().sum, 75);
+    assert_eq!(mt.leaf(3), Some((&"dave", 40)));
+
+    Ok(())
+}
+
+#[test]
+fn test_inclusion_proofs() -> Result<(), String> {
+    let mut mt = MerkleSumTree::<&str, Blake2b256>::new(3);
+    for (data, balance) in ACCOUNTS.iter() {
+        mt.add_data(*data, *balance).unwrap();
+    }
+    let root = mt.root();
+
+    for (i, (data, balance)) in ACCOUNTS.iter().enumerate() {
+        let proof = mt.generate_proof(i).unwrap();
+        assert_eq!(mt.verify(*data, *balance, &proof), Ok(true));
+        assert_eq!(mt.verify(*data, balance + 1, &proof), Ok(false));
+        assert_eq!(
+            verify_sum_proof::<Blake2b256>(&root, "mallory", *balance, &proof),
+            Ok(false)
+        );
+    }
+
+    // understating the total does not verify
+    let proof = mt.generate_proof(0).unwrap();
+    let understated = SumNode {
+        hash: root.hash,
+        sum: 70,
+    };
+    assert_eq!(
+        verify_sum_proof::<Blake2b256>(&understated, "alice", 10, &proof),
+        Ok(false)
+    );
+
+    assert_eq!(
+        mt.generate_proof(5),
+        Err(MerkleError::IndexOutOfRange { index: 5, len: 5 })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_hidden_balance() -> Result<(), String> {
+    let mut mt = MerkleSumTree::<&str, Sha256>::new(1);
+    mt.add_data("alice", 10).unwrap();
+    mt.add_data("bob", 20).unwrap();
+    let root = mt.root();
+
+    // a sibling claiming a sum that wraps around would hide alice's
+    // balance from the total
+    let mut proof = mt.generate_proof(0).unwrap();
+    proof.siblings[0].sum = u128::MAX - 9;
+    assert_eq!(
+        verify_sum_proof::<Sha256>(&root, "alice", 10, &proof),
+        Err(MerkleError::SumOverflow)
+    );
+
+    let mut proof = mt.generate_proof(0).unwrap();
+    proof.siblings[0].sum = 10;
+    assert_eq!(
+        verify_sum_proof::<Sha256>(&root, "alice", 10, &proof),
+        Ok(false)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_add_data_errors() -> Result<(), String> {
+    let mut mt = MerkleSumTree::<&str, Sha256>::new(1);
+
+    mt.add_data("alice", u128::MAX).unwrap();
+    assert_eq!(mt.add_data("bob", 1), Err(MerkleError::SumOverflow));
+    assert_eq!(mt.len(), 1);
+    mt.add_data("bob", 0).unwrap();
+    assert_eq!(mt.add_data("carol", 0), Err(MerkleError::TreeFull));
+
+    Ok(())
+}